@@ -0,0 +1,422 @@
+use board::{BoardGame, Compass, PlacementError};
+use cell::CellKind;
+use piece::PieceKind;
+use rng::Rng;
+use scan::ScanOrder;
+
+/// Settings of a simulated annealing run
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnealConfig {
+    pub seed: u64,
+    /// Temperature of the first iteration, a move losing `t` edges is then accepted with a
+    /// probability of `1/e`
+    pub start_temperature: f64,
+    /// Temperature of the last iteration, the temperature decreases geometrically in between
+    pub end_temperature: f64,
+    pub iterations: u64,
+}
+
+impl Default for AnnealConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            start_temperature: 2.0,
+            end_temperature: 0.05,
+            iterations: 1_000_000,
+        }
+    }
+}
+
+/// Change of the board tried at each iteration, applying it twice restores the board
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Move {
+    /// Exchanges the pieces of two cells of the same kind, each keeps its orientation
+    Swap((u16, u16), (u16, u16)),
+    /// Turns a full piece, `from` being its orientation before the move
+    Rotate {
+        pos: (u16, u16),
+        from: Compass,
+        to: Compass,
+    },
+    /// Exchanges two 2x2 blocks of full cells given by their north-west cell
+    SwapBlocks((u16, u16), (u16, u16)),
+}
+
+/// Local search maximizing `BoardGame::score` by simulated annealing.
+///
+/// The board is first filled at random, each piece on a cell of its kind, then pieces are
+/// swapped and rotated. Moves improving the score are always kept, the others with a probability
+/// decreasing with the temperature. Hints are never moved.
+#[derive(Debug)]
+pub struct Annealer {
+    board: BoardGame,
+    config: AnnealConfig,
+    rng: Rng,
+    iteration: u64,
+    score: u32,
+    best: BoardGame,
+    best_score: u32,
+    /// Movable cells grouped by kind: corners, borders and full cells
+    groups: [Vec<(u16, u16)>; 3],
+    /// North-west cells of the 2x2 blocks of movable full cells
+    blocks: Vec<(u16, u16)>,
+}
+
+impl Annealer {
+    /// Creates an annealer filling the empty cells of the board with the unplaced pieces.
+    ///
+    /// Pieces already on the board are the starting point, only hints stay in place.
+    ///
+    /// # Returns
+    ///
+    /// `Err` if the unplaced pieces do not have the kinds of the empty cells.
+    pub fn new(mut board: BoardGame, config: AnnealConfig) -> Result<Self, PlacementError> {
+        board.discard_history();
+        let mut rng = Rng::new(config.seed);
+
+        let group = |kind: CellKind| match kind {
+            CellKind::Corner(..) => 0,
+            CellKind::Border(_) => 1,
+            CellKind::Full => 2,
+        };
+        let mut cells: Vec<_> = board
+            .iter_cells(&ScanOrder::RowMajor)
+            .filter(|(_, cell)| cell.is_empty())
+            .map(|(pos, cell)| (group(cell.get_kind()), pos))
+            .collect();
+        cells.sort_by_key(|(group, _)| *group);
+
+        let mut pieces: Vec<_> = board
+            .pieces
+            .iter()
+            .filter(|piece| !board.placed[piece.get_props().id as usize])
+            .map(|piece| {
+                let group = match piece.get_kind() {
+                    PieceKind::Corner => 0,
+                    PieceKind::Border => 1,
+                    PieceKind::Full => 2,
+                };
+                (group, piece.get_props().id)
+            })
+            .collect();
+        rng.shuffle(&mut pieces);
+        pieces.sort_by_key(|(group, _)| *group);
+
+        for ((_, pos), (_, id)) in cells.into_iter().zip(pieces) {
+            let compass = Compass::all()[rng.below(4)];
+            board.put_piece(id, pos, Some(compass))?;
+        }
+
+        let mut groups = [Vec::new(), Vec::new(), Vec::new()];
+        for (pos, cell) in board.iter_cells(&ScanOrder::RowMajor) {
+            if !board.is_locked(pos) && !cell.is_empty() {
+                groups[group(cell.get_kind())].push(pos);
+            }
+        }
+        let blocks = groups[2]
+            .iter()
+            .cloned()
+            .filter(|&(x, y)| {
+                [(x + 1, y), (x, y + 1), (x + 1, y + 1)]
+                    .iter()
+                    .all(|pos| groups[2].contains(pos))
+            })
+            .collect();
+
+        let score = board.score();
+        Ok(Self {
+            best: board.clone(),
+            board,
+            config,
+            rng,
+            iteration: 0,
+            score,
+            best_score: score,
+            groups,
+            blocks,
+        })
+    }
+
+    /// Returns the board in its current state
+    pub fn board(&self) -> &BoardGame {
+        &self.board
+    }
+
+    /// Returns the score of the current board
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Returns the best board seen so far
+    pub fn best(&self) -> &BoardGame {
+        &self.best
+    }
+
+    pub fn best_score(&self) -> u32 {
+        self.best_score
+    }
+
+    /// Returns the number of iterations done
+    pub fn iteration(&self) -> u64 {
+        self.iteration
+    }
+
+    /// Runs the remaining iterations, stopping early once every edge matches.
+    ///
+    /// Returns the best score seen.
+    pub fn run(&mut self) -> u32 {
+        let max_score = self.board.max_score();
+        while self.iteration < self.config.iterations && self.best_score < max_score {
+            self.step();
+        }
+        self.best_score
+    }
+
+    /// Tries one move and keeps it or not depending on the temperature
+    pub fn step(&mut self) {
+        let temperature = self.temperature();
+        self.iteration += 1;
+
+        let change = match self.pick_move() {
+            Some(change) => change,
+            None => return,
+        };
+
+        let positions = Annealer::positions(&change);
+        let before = self.board.score_around(&positions);
+        self.apply(&change);
+        let after = self.board.score_around(&positions);
+
+        let delta = after as f64 - before as f64;
+        if delta >= 0.0 || self.rng.next_f64() < (delta / temperature).exp() {
+            self.score = self.score + after - before;
+            if self.score > self.best_score {
+                self.best_score = self.score;
+                self.best = self.board.clone();
+            }
+        } else {
+            self.apply(&Annealer::inverse(&change));
+        }
+    }
+
+    /// Returns the temperature of the current iteration
+    fn temperature(&self) -> f64 {
+        let (start, end) = (self.config.start_temperature, self.config.end_temperature);
+        let progress = match self.config.iterations {
+            0 => 1.0,
+            iterations => self.iteration as f64 / iterations as f64,
+        };
+        (start * (end / start).powf(progress)).max(f64::MIN_POSITIVE)
+    }
+
+    /// Draws a move: half swaps, the other half rotations and block swaps when the board has them
+    fn pick_move(&mut self) -> Option<Move> {
+        let draw = self.rng.below(10);
+        if draw >= 8 && self.blocks.len() > 1 {
+            let a = self.blocks[self.rng.below(self.blocks.len())];
+            let b = self.blocks[self.rng.below(self.blocks.len())];
+            let apart =
+                (a.0 as i32 - b.0 as i32).abs() >= 2 || (a.1 as i32 - b.1 as i32).abs() >= 2;
+            return if apart {
+                Some(Move::SwapBlocks(a, b))
+            } else {
+                None
+            };
+        }
+        if draw >= 5 && !self.groups[2].is_empty() {
+            let pos = self.groups[2][self.rng.below(self.groups[2].len())];
+            let from = self.compass(pos);
+            let to = Compass::all()[self.rng.below(4)];
+            return if from != to {
+                Some(Move::Rotate { pos, from, to })
+            } else {
+                None
+            };
+        }
+
+        let total = self.groups.iter().map(|group| group.len()).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut i = self.rng.below(total);
+        let group = self
+            .groups
+            .iter()
+            .find(|group| {
+                let found = i < group.len();
+                if !found {
+                    i -= group.len();
+                }
+                found
+            })
+            .unwrap();
+        let other = self.rng.below(group.len());
+        if other == i {
+            None
+        } else {
+            Some(Move::Swap(group[i], group[other]))
+        }
+    }
+
+    fn apply(&mut self, change: &Move) {
+        match *change {
+            Move::Swap(a, b) => self.swap(a, b),
+            Move::Rotate { pos, to, .. } => self.board.rotate_piece(pos, to),
+            Move::SwapBlocks((ax, ay), (bx, by)) => {
+                for &(dx, dy) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    self.swap((ax + dx, ay + dy), (bx + dx, by + dy));
+                }
+            }
+        }
+    }
+
+    fn inverse(change: &Move) -> Move {
+        match *change {
+            Move::Rotate { pos, from, to } => Move::Rotate {
+                pos,
+                from: to,
+                to: from,
+            },
+            change => change,
+        }
+    }
+
+    /// Returns the cells changed by the move
+    fn positions(change: &Move) -> Vec<(u16, u16)> {
+        match *change {
+            Move::Swap(a, b) => vec![a, b],
+            Move::Rotate { pos, .. } => vec![pos],
+            Move::SwapBlocks((ax, ay), (bx, by)) => [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .iter()
+                .flat_map(|&(dx, dy)| vec![(ax + dx, ay + dy), (bx + dx, by + dy)])
+                .collect(),
+        }
+    }
+
+    fn swap(&mut self, a: (u16, u16), b: (u16, u16)) {
+        let (compass_a, compass_b) = (self.compass(a), self.compass(b));
+        let piece_a = self
+            .board
+            .try_remove_piece(a)
+            .unwrap_or_else(|e| panic!("{}", e));
+        let piece_b = self
+            .board
+            .try_remove_piece(b)
+            .unwrap_or_else(|e| panic!("{}", e));
+        self.board
+            .put_piece(piece_b, a, Some(compass_b))
+            .unwrap_or_else(|e| panic!("{}", e));
+        self.board
+            .put_piece(piece_a, b, Some(compass_a))
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// Returns the orientation of the piece at the position, the one forced by the frame or the
+    /// one of the full piece
+    fn compass(&self, pos: (u16, u16)) -> Compass {
+        self.board.cells[pos.1 as usize][pos.0 as usize]
+            .get_compass()
+            .expect("annealed cells are never empty")
+    }
+}
+
+impl BoardGame {
+    /// Fills the board by simulated annealing and returns the best board seen.
+    ///
+    /// The board itself is left untouched, see `Annealer`.
+    pub fn anneal(&self, config: AnnealConfig) -> Result<BoardGame, PlacementError> {
+        let mut annealer = Annealer::new(self.clone(), config)?;
+        annealer.run();
+        Ok(annealer.best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::create_board;
+    use puzzle::Hint;
+
+    fn config(seed: u64, iterations: u64) -> AnnealConfig {
+        AnnealConfig {
+            seed,
+            iterations,
+            ..AnnealConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_random_fill() {
+        let annealer = Annealer::new(self::create_board(), self::config(3, 0)).unwrap();
+        let board = annealer.board();
+
+        assert!(board.placed.iter().all(|placed| *placed));
+        assert!(board
+            .iter_cells(&ScanOrder::RowMajor)
+            .all(|(_, cell)| cell.get_props().is_some()));
+        assert_eq!(annealer.score(), board.score());
+        assert_eq!(annealer.best_score(), board.score());
+    }
+
+    #[test]
+    fn test_score_tracking() {
+        let mut annealer = Annealer::new(self::create_board(), self::config(5, 500)).unwrap();
+
+        for _ in 0..500 {
+            annealer.step();
+            assert_eq!(annealer.score(), annealer.board().score());
+            assert!(annealer.best_score() >= annealer.score());
+            assert_eq!(annealer.best().score(), annealer.best_score());
+        }
+        assert_eq!(annealer.iteration(), 500);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let board = self::create_board();
+        let a = board.anneal(self::config(11, 2000)).unwrap();
+        let b = board.anneal(self::config(11, 2000)).unwrap();
+
+        assert_eq!(a.cells, b.cells);
+        assert!(board.placed.iter().all(|placed| !placed));
+    }
+
+    #[test]
+    fn test_anneal_solves_small_board() {
+        let board = self::create_board();
+        let best = board.anneal(self::config(1, 200_000)).unwrap();
+
+        assert_eq!(best.score(), best.max_score());
+        assert!(best.mismatches().is_empty());
+    }
+
+    #[test]
+    fn test_anneal_keeps_hints() {
+        let mut board = self::create_board();
+        let hint = Hint {
+            piece: 14,
+            pos: (2, 2),
+            compass: Compass::West,
+        };
+        board.add_hint(hint).unwrap();
+
+        let mut annealer = Annealer::new(board.clone(), self::config(2, 2000)).unwrap();
+        annealer.run();
+        assert_eq!(annealer.board().cells[2][2], board.cells[2][2]);
+        assert_eq!(annealer.best().cells[2][2], board.cells[2][2]);
+    }
+
+    #[test]
+    fn test_kind_mismatch() {
+        let content = "2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 1 1 1\n0 0 2 3";
+        let board = BoardGame::new(content.lines().map(|line| line.to_string()).collect());
+
+        assert_eq!(
+            Annealer::new(board, AnnealConfig::default()).map(|_| ()),
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Border,
+                cell: CellKind::Corner(::cell::Border::South, ::cell::Border::East)
+            })
+        );
+    }
+}
@@ -1,13 +1,30 @@
-use cell::{Border, Cell, Face};
+use std::sync::Arc;
+
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+use cell::{Border, Cell, CellKind, Face};
+use history::{History, Move};
+use index::CandidateIndex;
 use piece;
-use piece::Piece;
+use piece::{Piece, PieceKind};
+use puzzle::{Hint, Puzzle, PuzzleFile};
+use scan::ScanOrder;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BoardGame {
-    pub size: u8,
+    pub width: u16,
+    pub height: u16,
     pub pieces: Vec<Piece>,
     pub placed: Vec<bool>,
     pub cells: Vec<Vec<Cell>>,
+    /// Pieces fixed on the board, they cannot be removed nor rotated
+    pub hints: Vec<Hint>,
+    /// Candidates of `pieces` by cell, built along with the board
+    pub index: Arc<CandidateIndex>,
+    /// Moves done on the board, see `BoardGame::undo`
+    pub(crate) history: History,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -18,73 +35,233 @@ pub enum Compass {
     West,
 }
 
+/// Reason why a piece cannot be put on the board
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PlacementError {
+    PieceAlreadyPlaced {
+        id: u16,
+    },
+    CellOccupied {
+        pos: (u16, u16),
+    },
+    KindMismatch {
+        piece: PieceKind,
+        cell: CellKind,
+    },
+    /// A full piece was given without compass
+    MissingOrientation,
+    /// The `found` face of the piece on `side` differs from the `expected` face of the neighbor
+    EdgeMismatch {
+        side: Border,
+        expected: Face,
+        found: Face,
+    },
+    UnknownPiece {
+        id: u16,
+    },
+    OutOfBounds {
+        pos: (u16, u16),
+    },
+}
+
+/// Reason why a placed piece cannot be rotated or removed, or a recorded move cannot be played
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MoveError {
+    OutOfBounds {
+        pos: (u16, u16),
+    },
+    EmptyCell {
+        pos: (u16, u16),
+    },
+    /// Only full pieces can be rotated
+    NotRotatable {
+        pos: (u16, u16),
+    },
+    /// The cell holds a hint
+    Locked {
+        pos: (u16, u16),
+    },
+    /// The cell holds the `found` piece where a move expected the `expected` one
+    PieceMismatch {
+        pos: (u16, u16),
+        expected: u16,
+        found: u16,
+    },
+    /// The piece of the move cannot be put on the board
+    Placement(PlacementError),
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlacementError::PieceAlreadyPlaced { id } => {
+                write!(f, "piece {} is already placed", id)
+            }
+            PlacementError::CellOccupied { pos } => {
+                write!(f, "already a piece placed at {:?}", pos)
+            }
+            PlacementError::KindMismatch { piece, cell } => {
+                write!(f, "cannot put {} piece on {} cell", piece, cell)
+            }
+            PlacementError::MissingOrientation => {
+                write!(f, "no facing specified for full piece placement")
+            }
+            PlacementError::EdgeMismatch {
+                side,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{:?} side does not match: expected {:?}, found {:?}",
+                side, expected, found
+            ),
+            PlacementError::UnknownPiece { id } => write!(f, "there is no piece {}", id),
+            PlacementError::OutOfBounds { pos } => write!(f, "{:?} is outside the board", pos),
+        }
+    }
+}
+
+impl Error for PlacementError {}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::OutOfBounds { pos } => write!(f, "{:?} is outside the board", pos),
+            MoveError::EmptyCell { pos } => {
+                write!(f, "cannot remove piece from empty cell {:?}", pos)
+            }
+            MoveError::NotRotatable { pos } => {
+                write!(f, "cannot rotate empty/corner/border cell {:?}", pos)
+            }
+            MoveError::Locked { pos } => write!(f, "cannot move the hint at {:?}", pos),
+            MoveError::PieceMismatch {
+                pos,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected piece {} at {:?}, found piece {}",
+                expected, pos, found
+            ),
+            MoveError::Placement(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+impl From<PlacementError> for MoveError {
+    fn from(error: PlacementError) -> Self {
+        MoveError::Placement(error)
+    }
+}
+
+impl Compass {
+    /// Returns the four orientations, clockwise from `North`
+    pub fn all() -> [Compass; 4] {
+        [Compass::North, Compass::East, Compass::South, Compass::West]
+    }
+
+    /// Returns the letter of the orientation in files: `N`, `E`, `S` or `W`
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Compass::North => "N",
+            Compass::East => "E",
+            Compass::South => "S",
+            Compass::West => "W",
+        }
+    }
+
+    /// Reads an orientation written by `Compass::letter`
+    pub fn from_letter(letter: &str) -> Option<Compass> {
+        Compass::all()
+            .iter()
+            .find(|compass| compass.letter() == letter)
+            .cloned()
+    }
+}
+
 impl BoardGame {
     /// Creates the board based on file lines
+    ///
+    /// # Panics
+    ///
+    /// if the content is not a valid puzzle file, see `PuzzleFile::parse`
     pub fn new(content: Vec<String>) -> Self {
-        // println!("{:?}", &content[4..]);
+        let puzzle = PuzzleFile::parse(&content.join("\n")).unwrap_or_else(|e| panic!("{}", e));
 
-        let size = content[0].parse::<u8>().expect("must be a number");
-        let pieces = content[4..]
-            .iter()
-            .enumerate()
-            .map(|(i, line)| {
-                Piece::new(
-                    i as u8,
-                    line.split_whitespace()
-                        .map(|nb| nb.parse::<u8>().expect("must be a number"))
-                        .collect(),
-                )
+        BoardGame::from_puzzle(puzzle)
+    }
+
+    /// Creates a board for the pieces of the puzzle, with only its hints placed
+    ///
+    /// # Panics
+    ///
+    /// if a hint cannot be put on the board, hints read by `PuzzleFile::parse` always can
+    pub fn from_puzzle(puzzle: Puzzle) -> Self {
+        let Puzzle {
+            width,
+            height,
+            pieces,
+            hints,
+            ..
+        } = puzzle;
+
+        let placed = vec![false; (width as usize) * (height as usize)];
+
+        let cells = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| Cell::empty(CellKind::at((x, y), width, height)))
+                    .collect()
             })
             .collect();
 
-        let placed = vec![false; (size as usize) * (size as usize)];
-
-        let last_index = size - 1;
-
-        use cell::Border::{East, North, South, West};
-
-        let mut cells = Vec::with_capacity(size as usize);
-        for x in 0..size {
-            let mut row = Vec::with_capacity(size as usize);
-            for y in 0..size {
-                row.push(
-                    if (x == 0 || x == last_index) && (y == 0 || y == last_index) {
-                        Cell::CornerCell(
-                            None,
-                            (
-                                if x == 0 { North } else { South },
-                                if y == 0 { West } else { East },
-                            ),
-                        )
-                    } else if x == 0 || x == last_index || y == 0 || y == last_index {
-                        Cell::BorderCell(
-                            None,
-                            if x == 0 {
-                                North
-                            } else if x == last_index {
-                                South
-                            } else if y == 0 {
-                                West
-                            } else if y == last_index {
-                                East
-                            } else {
-                                unreachable!()
-                            },
-                        )
-                    } else {
-                        Cell::FullCell(None, None)
-                    },
-                );
-            }
-            cells.push(row);
-        }
+        let index = Arc::new(CandidateIndex::new(&pieces));
 
-        Self {
-            size,
+        let mut board = Self {
+            width,
+            height,
             pieces,
             placed,
             cells,
+            hints: Vec::with_capacity(hints.len()),
+            index,
+            history: History::new(),
+        };
+        for hint in hints {
+            board.add_hint(hint).unwrap_or_else(|e| panic!("{}", e));
         }
+
+        board
+    }
+
+    /// Puts a piece on the board and locks its cell.
+    ///
+    /// Like `BoardGame::put_piece`, the hint is not checked against its neighbors. Solvers keep
+    /// the hints as they are, `BoardGame::try_remove_piece` and `BoardGame::try_rotate_piece` refuse
+    /// to move them.
+    pub fn add_hint(&mut self, hint: Hint) -> Result<(), PlacementError> {
+        self.set_piece(hint.piece, hint.pos, Some(hint.compass))?;
+        self.hints.push(hint);
+
+        Ok(())
+    }
+
+    /// Checks if the cell holds a hint
+    pub fn is_locked(&self, pos: (u16, u16)) -> bool {
+        self.hints.iter().any(|hint| hint.pos == pos)
+    }
+
+    /// Iterates over the positions and cells of the board in the given order
+    pub fn iter_cells<'a>(
+        &'a self,
+        order: &ScanOrder,
+    ) -> impl Iterator<Item = ((u16, u16), &'a Cell)> + 'a {
+        order
+            .positions(self.width, self.height)
+            .into_iter()
+            .map(move |(x, y)| ((x, y), &self.cells[y as usize][x as usize]))
     }
 
     /// Returns the current neighborhood of the given position
@@ -92,20 +269,20 @@ impl BoardGame {
     /// # Panics
     ///
     /// if the retrieved cells have some kind of errors (ex: bad piece <-> cell allocation)
-    pub fn get_frontier(&self, pos: (u8, u8)) -> (Face, Face, Face, Face) {
+    pub fn get_frontier(&self, pos: (u16, u16)) -> (Face, Face, Face, Face) {
         let (x, y) = pos;
-        let last_index = self.size - 1;
+        let (last_x, last_y) = (self.width - 1, self.height - 1);
         (
             match y {
                 0 => Face::Border,
                 _ => self.cells[(y - 1) as usize][x as usize].get_face(Border::South),
             },
             match x {
-                a if a == last_index => Face::Border,
+                a if a == last_x => Face::Border,
                 _ => self.cells[y as usize][(x + 1) as usize].get_face(Border::West),
             },
             match y {
-                a if a == last_index => Face::Border,
+                a if a == last_y => Face::Border,
                 _ => self.cells[(y + 1) as usize][x as usize].get_face(Border::North),
             },
             match x {
@@ -115,41 +292,64 @@ impl BoardGame {
         )
     }
 
+    /// Returns the oriented pieces matching the north and west neighbors of the given position.
+    ///
+    /// Neighbors that are still empty accept any color. Pieces already placed are part of the
+    /// result, as well as pieces that do not match the south and east neighbors.
+    pub fn candidates(&self, pos: (u16, u16)) -> &[(u16, Compass)] {
+        let (x, y) = pos;
+        let (north, _, _, west) = self.get_frontier(pos);
+        let kind = self.cells[y as usize][x as usize].get_kind();
+
+        self.index.get(north, west, kind)
+    }
+
     /// Places a piece and checks if the piece can be placed.
     ///
     /// # Returns
     ///
-    /// `Err` if the piece cannot be placed.
+    /// `Err` if the piece cannot be placed, see `BoardGame::put_piece`, or if one of its sides does
+    /// not match the neighborhood.
     pub fn place_piece(
         &mut self,
-        piece: u8,
-        pos: (u8, u8),
+        piece: u16,
+        pos: (u16, u16),
         compass: Option<Compass>,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), PlacementError> {
+        self.check_put(piece, pos, compass)?;
+
         let (x, y) = pos;
         let internal_orientation = self.cells[y as usize][x as usize]
             .get_compass()
             .or(compass)
-            .expect("should have a compass");
+            .ok_or(PlacementError::MissingOrientation)?;
 
         let (a, b, c, d) = self.pieces[piece as usize].get_faces(&internal_orientation);
         let (a_check, b_check, c_check, d_check) = self.get_frontier(pos);
 
-        if (a_check == a || a_check == Face::None)
-            && (b_check == b || b_check == Face::None)
-            && (c_check == c || c_check == Face::None)
-            && (d_check == d || d_check == Face::None)
-        {
-            return self.put_piece(piece, pos, compass);
+        for (side, expected, found) in &[
+            (Border::North, a_check, a),
+            (Border::East, b_check, b),
+            (Border::South, c_check, c),
+            (Border::West, d_check, d),
+        ] {
+            if expected != found && *expected != Face::None {
+                return Err(PlacementError::EdgeMismatch {
+                    side: *side,
+                    expected: *expected,
+                    found: *found,
+                });
+            }
         }
 
-        //let piece_borders = cell.get_faces(piece_props, compass);
-        //if frontier == piece {
-        //    self.put_piece(piece, pos, compass)
-        //} else {
-        //    Err("borders do not match")
-        //}
-        Err("cannot put piece at this position")
+        self.set_piece(piece, pos, compass)?;
+        self.history.record(Move::Place {
+            piece,
+            pos,
+            compass,
+        });
+
+        Ok(())
     }
 
     /// Puts a piece on the board, it does not check if the piece can be placed depending on its neighbors.
@@ -157,66 +357,92 @@ impl BoardGame {
     /// # Examples
     ///
     /// ```
-    /// board.put_piece(1, (0, 0), None);
+    /// # use rust_eternity::{BoardGame, Compass, PuzzleFile};
+    /// # let content = "4 4\n5\n1\n1 1 1 1\n0 0 1 1\n0 0 1 2\n0 0 2 1\n0 0 2 2\n0 1 3 1\n0 1 3 2\n\
+    /// #     0 1 4 1\n0 1 5 2\n0 2 4 1\n0 2 4 2\n0 2 5 1\n0 2 5 2\n3 3 5 5\n3 4 3 5\n3 4 4 4\n3 5 5 4";
+    /// # let mut board = BoardGame::from_puzzle(PuzzleFile::parse(content).unwrap());
+    /// board.put_piece(1, (0, 0), None).unwrap();
+    /// assert!(board.placed[1]);
     /// ```
     ///
     /// Returns `Err` if
     ///
+    /// - the piece or the position do not exist
     /// - the piece is already placed somewhere else
-    /// - the piece is not the same type as the cell
     /// - there is already a piece placed at this position
+    /// - the piece is not the same type as the cell
+    /// - no compass is given for a full piece
     pub fn put_piece(
         &mut self,
-        index: u8,
-        pos: (u8, u8),
+        index: u16,
+        pos: (u16, u16),
         compass: Option<Compass>,
-    ) -> Result<(), &'static str> {
-        if self.placed[index as usize] {
-            return Err("this piece is already placed");
-        }
+    ) -> Result<(), PlacementError> {
+        self.set_piece(index, pos, compass)?;
+        self.history.record(Move::Put {
+            piece: index,
+            pos,
+            compass,
+        });
+
+        Ok(())
+    }
+
+    /// Puts a piece on the board without recording the move
+    pub(crate) fn set_piece(
+        &mut self,
+        index: u16,
+        pos: (u16, u16),
+        compass: Option<Compass>,
+    ) -> Result<(), PlacementError> {
+        self.check_put(index, pos, compass)?;
 
         let (x, y) = pos;
-        let piece = self.pieces[index as usize];
+        let props = self.pieces[index as usize].get_props();
         match self.cells[y as usize][x as usize] {
-            Cell::CornerCell(ref mut a @ None, _) => match piece {
-                Piece::FullPiece(_) => Err("cannot put full piece on corner cell"),
-                Piece::BorderPiece(_) => Err("cannot put border piece on corner cell"),
-
-                Piece::CornerPiece(props) => {
-                    *a = Some(props);
-                    self.placed[index as usize] = true;
+            Cell::CornerCell(ref mut a, _) | Cell::BorderCell(ref mut a, _) => *a = Some(props),
+            Cell::FullCell(ref mut a, ref mut facing) => {
+                *a = Some(props);
+                *facing = compass;
+            }
+        }
+        self.placed[index as usize] = true;
 
-                    Ok(())
-                }
-            },
-            Cell::BorderCell(ref mut a @ None, _) => match piece {
-                Piece::CornerPiece(_) => Err("cannot put corner piece on border cell"),
-                Piece::FullPiece(_) => Err("cannot put full piece on border cell"),
+        Ok(())
+    }
 
-                Piece::BorderPiece(props) => {
-                    *a = Some(props);
-                    self.placed[index as usize] = true;
+    /// Checks everything `BoardGame::put_piece` needs, in the order the errors are reported
+    fn check_put(
+        &self,
+        index: u16,
+        pos: (u16, u16),
+        compass: Option<Compass>,
+    ) -> Result<(), PlacementError> {
+        if index as usize >= self.pieces.len() {
+            return Err(PlacementError::UnknownPiece { id: index });
+        }
+        self.check_pos(pos)?;
+        if self.placed[index as usize] {
+            return Err(PlacementError::PieceAlreadyPlaced { id: index });
+        }
 
-                    Ok(())
-                }
-            },
-            Cell::FullCell(ref mut a @ None, ref mut facing) => match piece {
-                Piece::CornerPiece(_) => Err("cannot put corner piece on full cell"),
-                Piece::BorderPiece(_) => Err("cannot put border piece on full cell"),
-
-                Piece::FullPiece(props) => {
-                    *facing = match compass {
-                        None => return Err("no facing specified for full piece placement"),
-                        _ => compass,
-                    };
-                    *a = Some(props);
-                    self.placed[index as usize] = true;
-
-                    Ok(())
-                }
-            },
-            _ => Err("already a piece placed"),
+        let (x, y) = pos;
+        let cell = &self.cells[y as usize][x as usize];
+        let piece = &self.pieces[index as usize];
+        if !cell.is_empty() {
+            return Err(PlacementError::CellOccupied { pos });
+        }
+        if !cell.accepts(piece) {
+            return Err(PlacementError::KindMismatch {
+                piece: piece.get_kind(),
+                cell: cell.get_kind(),
+            });
+        }
+        if cell.get_kind() == CellKind::Full && compass.is_none() {
+            return Err(PlacementError::MissingOrientation);
         }
+
+        Ok(())
     }
 
     /// Rotates an already placed piece
@@ -224,50 +450,140 @@ impl BoardGame {
     /// # Example
     ///
     /// ```
-    /// board.put_piece(13, (1,1), Some(Compass::North));
-    /// board.rotate((1,1), Compass:South);
+    /// # use rust_eternity::{BoardGame, Compass, PuzzleFile};
+    /// # let content = "4 4\n5\n1\n1 1 1 1\n0 0 1 1\n0 0 1 2\n0 0 2 1\n0 0 2 2\n0 1 3 1\n0 1 3 2\n\
+    /// #     0 1 4 1\n0 1 5 2\n0 2 4 1\n0 2 4 2\n0 2 5 1\n0 2 5 2\n3 3 5 5\n3 4 3 5\n3 4 4 4\n3 5 5 4";
+    /// # let mut board = BoardGame::from_puzzle(PuzzleFile::parse(content).unwrap());
+    /// board.put_piece(13, (1, 1), Some(Compass::North)).unwrap();
+    /// board.rotate_piece((1, 1), Compass::South);
+    /// assert_eq!(board.cells[1][1].get_compass(), Some(Compass::South));
     /// ```
     ///
     /// # Panics
     ///
     /// - there is no piece at this position
     /// - the piece cannot be rotated (corner or border cell)
-    pub fn rotate_piece(&mut self, pos: (u8, u8), compass: Compass) {
+    /// - the cell holds a hint
+    pub fn rotate_piece(&mut self, pos: (u16, u16), compass: Compass) {
+        self.try_rotate_piece(pos, compass)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Rotates an already placed piece, see `BoardGame::rotate_piece`
+    ///
+    /// Returns `Err` if the position is outside the board, holds a hint or does not hold a full
+    /// piece.
+    pub fn try_rotate_piece(&mut self, pos: (u16, u16), compass: Compass) -> Result<(), MoveError> {
+        let from = self.turn_piece(pos, compass)?;
+        self.history.record(Move::Rotate {
+            pos,
+            from,
+            to: compass,
+        });
+
+        Ok(())
+    }
+
+    /// Rotates an already placed piece without recording the move, returns its former compass
+    pub(crate) fn turn_piece(
+        &mut self,
+        pos: (u16, u16),
+        compass: Compass,
+    ) -> Result<Compass, MoveError> {
+        self.check_move(pos)?;
+
         let (x, y) = pos;
         match self.cells[y as usize][x as usize] {
-            Cell::FullCell(Some(_), Some(ref mut a)) => *a = compass,
-            _ => panic!("cannot rotate empty/corner/border cell"),
+            Cell::FullCell(Some(_), Some(ref mut a)) => Ok(mem::replace(a, compass)),
+            _ => Err(MoveError::NotRotatable { pos }),
         }
     }
 
     /// Removes the piece placed on the position
     ///
     /// # Example
+    ///
     /// ```
-    /// board.put_piece(13, (1, 1), Some(Compass::North)); // places a piece at (1, 1)
-    /// board.remove_piece((1, 1)) // removes the placed piece
+    /// # use rust_eternity::{BoardGame, Compass, PuzzleFile};
+    /// # let content = "4 4\n5\n1\n1 1 1 1\n0 0 1 1\n0 0 1 2\n0 0 2 1\n0 0 2 2\n0 1 3 1\n0 1 3 2\n\
+    /// #     0 1 4 1\n0 1 5 2\n0 2 4 1\n0 2 4 2\n0 2 5 1\n0 2 5 2\n3 3 5 5\n3 4 3 5\n3 4 4 4\n3 5 5 4";
+    /// # let mut board = BoardGame::from_puzzle(PuzzleFile::parse(content).unwrap());
+    /// board.put_piece(13, (1, 1), Some(Compass::North)).unwrap(); // places a piece at (1, 1)
+    /// board.remove_piece((1, 1)); // removes the placed piece
+    /// assert!(board.cells[1][1].is_empty());
     /// ```
     ///
     /// # Panics
     ///
     /// - there is no piece at this position
-    pub fn remove_piece(&mut self, pos: (u8, u8)) {
+    /// - the cell holds a hint
+    pub fn remove_piece(&mut self, pos: (u16, u16)) {
+        self.try_remove_piece(pos)
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// Removes the piece placed on the position, see `BoardGame::remove_piece`
+    ///
+    /// Returns the id of the removed piece, or `Err` if the position is outside the board, empty or
+    /// holds a hint.
+    pub fn try_remove_piece(&mut self, pos: (u16, u16)) -> Result<u16, MoveError> {
+        let (piece, compass) = self.take_piece(pos)?;
+        self.history.record(Move::Remove {
+            piece,
+            pos,
+            compass,
+        });
+
+        Ok(piece)
+    }
+
+    /// Removes the piece placed on the position without recording the move, returns its id and
+    /// the compass of a full piece
+    pub(crate) fn take_piece(
+        &mut self,
+        pos: (u16, u16),
+    ) -> Result<(u16, Option<Compass>), MoveError> {
+        self.check_move(pos)?;
+
         let (x, y) = pos;
+        let mut facing = None;
         let p = match self.cells[y as usize][x as usize] {
             Cell::CornerCell(ref mut a, _) => a,
             Cell::BorderCell(ref mut a, _) => a,
-            Cell::FullCell(ref mut a, ref mut compass) => {
-                *compass = None;
+            Cell::FullCell(ref mut a @ Some(_), ref mut compass) => {
+                facing = compass.take();
                 a
             }
+            Cell::FullCell(ref mut a, _) => a,
         };
 
-        match p {
-            Some(piece::Props { id, .. }) => self.placed[*id as usize] = false,
-            None => panic!("cannot remove piece from empty cell"),
+        let id = match p.take() {
+            Some(piece::Props { id, .. }) => id,
+            None => return Err(MoveError::EmptyCell { pos }),
         };
+        self.placed[id as usize] = false;
+
+        Ok((id, facing))
+    }
 
-        *p = None;
+    fn check_pos(&self, pos: (u16, u16)) -> Result<(), PlacementError> {
+        if pos.0 >= self.width || pos.1 >= self.height {
+            return Err(PlacementError::OutOfBounds { pos });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the piece on the position can be moved: inside the board and not a hint
+    pub(crate) fn check_move(&self, pos: (u16, u16)) -> Result<(), MoveError> {
+        if pos.0 >= self.width || pos.1 >= self.height {
+            return Err(MoveError::OutOfBounds { pos });
+        }
+        if self.is_locked(pos) {
+            return Err(MoveError::Locked { pos });
+        }
+
+        Ok(())
     }
 }
 
@@ -283,9 +599,10 @@ mod tests {
     #[test]
     fn test_create_board() {
         let board = tests::create_board();
-        let last: usize = (board.size - 1) as usize;
+        let last: usize = (board.width - 1) as usize;
 
-        assert_eq!(board.size, 4);
+        assert_eq!(board.width, 4);
+        assert_eq!(board.height, 4);
         assert_eq!(board.pieces.len(), 16);
         assert_eq!(board.cells.len(), 4);
 
@@ -347,6 +664,85 @@ mod tests {
         );
     }
 
+    #[test]
+    #[should_panic(expected = "line 3, column 1: missing third header")]
+    fn test_create_board_panic() {
+        BoardGame::new(vec!["4".to_string(), "5".to_string()]);
+    }
+
+    #[test]
+    fn test_create_rectangular_board() {
+        let content = "4 3\n5\n1\n1 1 1 1\n0 0 1 1\n0 0 1 2\n0 0 2 1\n0 0 2 2\n0 1 3 1\n0 1 3 2\n0 1 4 1\n0 1 5 2\n0 2 4 1\n0 2 4 2\n3 3 5 5\n3 4 3 5";
+        let mut board = BoardGame::new(content.lines().map(|line| line.to_string()).collect());
+
+        assert_eq!((board.width, board.height), (4, 3));
+        assert_eq!(board.cells.len(), 3);
+        assert!(board.cells.iter().all(|row| row.len() == 4));
+
+        assert_eq!(
+            board.cells[0][3],
+            Cell::CornerCell(None, (Border::North, Border::East))
+        );
+        assert_eq!(
+            board.cells[2][0],
+            Cell::CornerCell(None, (Border::South, Border::West))
+        );
+        assert_eq!(board.cells[0][2], Cell::BorderCell(None, Border::North));
+        assert_eq!(board.cells[2][1], Cell::BorderCell(None, Border::South));
+        assert_eq!(board.cells[1][0], Cell::BorderCell(None, Border::West));
+        assert_eq!(board.cells[1][3], Cell::BorderCell(None, Border::East));
+        assert_eq!(board.cells[1][1], Cell::FullCell(None, None));
+        assert_eq!(board.cells[1][2], Cell::FullCell(None, None));
+
+        assert_eq!(
+            board.get_frontier((3, 2)),
+            (Face::None, Face::Border, Face::Border, Face::None)
+        );
+        assert_eq!(
+            board.try_remove_piece((4, 0)),
+            Err(MoveError::OutOfBounds { pos: (4, 0) })
+        );
+        assert_eq!(
+            board.try_remove_piece((0, 3)),
+            Err(MoveError::OutOfBounds { pos: (0, 3) })
+        );
+    }
+
+    #[test]
+    fn test_large_board() {
+        let size = 17;
+        let mut pieces = Vec::new();
+        for i in 0..(size * size) {
+            let sides = match i {
+                0..=3 => vec![0, 0, 300, 301],
+                4..=63 => vec![0, 300, 302, 301],
+                _ => vec![302, 302, 302, 302],
+            };
+            pieces.push(Piece::new(i, sides));
+        }
+        let mut board = BoardGame::from_puzzle(Puzzle {
+            width: size,
+            height: size,
+            colors: 302,
+            pieces,
+            hints: Vec::new(),
+        });
+
+        board
+            .put_piece(288, (16, 15), Some(Compass::North))
+            .unwrap_err();
+        board
+            .put_piece(288, (15, 15), Some(Compass::North))
+            .unwrap();
+        assert!(board.placed[288]);
+        assert!(!board.placed[288 % 256]);
+        assert_eq!(
+            board.cells[15][15].get_props().map(|props| props.id),
+            Some(288)
+        );
+        assert_eq!(board.get_frontier((15, 14)).2, Face::Color(302));
+    }
+
     #[test]
     fn test_place_piece() {
         let mut board = self::create_board();
@@ -354,22 +750,57 @@ mod tests {
         assert_eq!(board.place_piece(4, (0, 1), None), Ok(()));
         assert_eq!(
             board.place_piece(5, (1, 0), None),
-            Err("cannot put piece at this position")
+            Err(PlacementError::EdgeMismatch {
+                side: Border::West,
+                expected: Face::Color(1),
+                found: Face::Color(2),
+            })
         );
         assert_eq!(board.place_piece(6, (1, 0), None), Ok(()));
         assert_eq!(
             board.place_piece(14, (1, 1), Some(Compass::North)),
-            Err("cannot put piece at this position")
+            Err(PlacementError::EdgeMismatch {
+                side: Border::North,
+                expected: Face::Color(4),
+                found: Face::Color(3),
+            })
         );
         assert_eq!(board.place_piece(14, (1, 1), Some(Compass::West)), Ok(()));
     }
 
     #[test]
-    #[should_panic(expected = "should have a compass")]
-    fn test_place_piece_panic() {
+    fn test_place_piece_errors() {
         let mut board = self::create_board();
 
-        board.place_piece(14, (1, 1), None).unwrap();
+        assert_eq!(
+            board.place_piece(14, (1, 1), None),
+            Err(PlacementError::MissingOrientation)
+        );
+        assert_eq!(
+            board.place_piece(14, (0, 0), None),
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Full,
+                cell: CellKind::Corner(Border::North, Border::West),
+            })
+        );
+
+        board.place_piece(0, (0, 0), None).unwrap();
+        assert_eq!(
+            board.place_piece(0, (3, 0), None),
+            Err(PlacementError::PieceAlreadyPlaced { id: 0 })
+        );
+        assert_eq!(
+            board.place_piece(1, (0, 0), None),
+            Err(PlacementError::CellOccupied { pos: (0, 0) })
+        );
+        assert_eq!(
+            board.place_piece(16, (1, 0), None),
+            Err(PlacementError::UnknownPiece { id: 16 })
+        );
+        assert_eq!(
+            board.place_piece(1, (4, 0), None),
+            Err(PlacementError::OutOfBounds { pos: (4, 0) })
+        );
     }
 
     #[test]
@@ -437,49 +868,178 @@ mod tests {
         board.put_piece(0, (0, 0), None).unwrap();
         assert_eq!(
             board.put_piece(1, (0, 0), None),
-            Err("already a piece placed")
+            Err(PlacementError::CellOccupied { pos: (0, 0) })
         );
         assert_eq!(
             board.put_piece(0, (0, 3), None),
-            Err("this piece is already placed")
+            Err(PlacementError::PieceAlreadyPlaced { id: 0 })
         );
         board.remove_piece((0, 0));
 
         assert_eq!(
             board.put_piece(15, (3, 3), Some(Compass::North)),
-            Err("cannot put full piece on corner cell")
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Full,
+                cell: CellKind::Corner(Border::South, Border::East),
+            })
         );
         assert_eq!(
             board.put_piece(4, (0, 0), None),
-            Err("cannot put border piece on corner cell")
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Border,
+                cell: CellKind::Corner(Border::North, Border::West),
+            })
         );
         assert_eq!(
             board.put_piece(0, (1, 0), None),
-            Err("cannot put corner piece on border cell")
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Corner,
+                cell: CellKind::Border(Border::North),
+            })
         );
         assert_eq!(
             board.put_piece(14, (2, 0), Some(Compass::North)),
-            Err("cannot put full piece on border cell")
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Full,
+                cell: CellKind::Border(Border::North),
+            })
         );
         assert_eq!(
             board.put_piece(3, (1, 1), None),
-            Err("cannot put corner piece on full cell")
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Corner,
+                cell: CellKind::Full,
+            })
         );
         assert_eq!(
             board.put_piece(5, (2, 2), None),
-            Err("cannot put border piece on full cell")
+            Err(PlacementError::KindMismatch {
+                piece: PieceKind::Border,
+                cell: CellKind::Full,
+            })
         );
 
         assert_eq!(
             board.put_piece(14, (2, 2), None),
-            Err("no facing specified for full piece placement")
+            Err(PlacementError::MissingOrientation)
+        );
+    }
+
+    #[test]
+    fn test_placement_error_display() {
+        let error = PlacementError::KindMismatch {
+            piece: PieceKind::Full,
+            cell: CellKind::Corner(Border::North, Border::West),
+        };
+        assert_eq!(error.to_string(), "cannot put full piece on corner cell");
+
+        let error = PlacementError::EdgeMismatch {
+            side: Border::East,
+            expected: Face::Color(1),
+            found: Face::Color(2),
+        };
+        assert_eq!(
+            error.to_string(),
+            "East side does not match: expected Color(1), found Color(2)"
         );
     }
 
+    #[test]
+    fn test_try_remove_rotate_piece() {
+        let mut board = self::create_board();
+        board.put_piece(14, (1, 1), Some(Compass::North)).unwrap();
+
+        assert_eq!(board.try_rotate_piece((1, 1), Compass::East), Ok(()));
+        assert_eq!(
+            board.try_rotate_piece((2, 2), Compass::East),
+            Err(MoveError::NotRotatable { pos: (2, 2) })
+        );
+        assert_eq!(
+            board.try_rotate_piece((4, 2), Compass::East),
+            Err(MoveError::OutOfBounds { pos: (4, 2) })
+        );
+
+        assert_eq!(board.try_remove_piece((1, 1)), Ok(14));
+        assert!(!board.placed[14]);
+        assert_eq!(board.cells[1][1], Cell::FullCell(None, None));
+        assert_eq!(
+            board.try_remove_piece((1, 1)),
+            Err(MoveError::EmptyCell { pos: (1, 1) })
+        );
+        assert_eq!(
+            board.try_remove_piece((0, 9)),
+            Err(MoveError::OutOfBounds { pos: (0, 9) })
+        );
+    }
+
+    #[test]
+    fn test_hints() {
+        let mut board = self::create_board();
+        let hint = Hint {
+            piece: 14,
+            pos: (1, 1),
+            compass: Compass::East,
+        };
+
+        assert_eq!(board.add_hint(hint), Ok(()));
+        assert!(board.is_locked((1, 1)));
+        assert!(!board.is_locked((2, 1)));
+        assert_eq!(board.cells[1][1].get_compass(), Some(Compass::East));
+        assert_eq!(
+            board.try_rotate_piece((1, 1), Compass::North),
+            Err(MoveError::Locked { pos: (1, 1) })
+        );
+        assert_eq!(
+            board.try_remove_piece((1, 1)),
+            Err(MoveError::Locked { pos: (1, 1) })
+        );
+        assert_eq!(
+            board.add_hint(Hint {
+                pos: (2, 1),
+                ..hint
+            }),
+            Err(PlacementError::PieceAlreadyPlaced { id: 14 })
+        );
+        assert_eq!(board.hints, vec![hint]);
+    }
+
+    #[test]
+    fn test_hints_from_file() {
+        let content =
+            include_str!("../pieces_4x4.txt").to_string() + "\nhint 0 0 0 N\nhint 12 1 1 S\n";
+        let board = BoardGame::new(content.lines().map(|line| line.to_string()).collect());
+
+        assert_eq!(board.hints.len(), 2);
+        assert!(board.placed[0] && board.placed[12]);
+        assert!(board.is_locked((0, 0)) && board.is_locked((1, 1)));
+        assert_eq!(board.cells[1][1].get_compass(), Some(Compass::South));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot move the hint at (1, 1)")]
+    fn test_remove_hint_panic() {
+        let mut board = self::create_board();
+        board
+            .add_hint(Hint {
+                piece: 14,
+                pos: (1, 1),
+                compass: Compass::North,
+            })
+            .unwrap();
+        board.remove_piece((1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot remove piece from empty cell")]
+    fn test_remove_piece_empty_panic() {
+        let mut board = self::create_board();
+        board.remove_piece((1, 1));
+    }
+
     #[test]
     fn test_frontier() {
         let mut board = self::create_board();
-        let size = board.size - 1;
+        let size = board.width - 1;
         board.put_piece(0, (0, 0), None).unwrap();
         board.put_piece(4, (size, 1), None).unwrap();
         board.put_piece(15, (1, 1), Some(Compass::North)).unwrap();
@@ -514,6 +1074,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_candidates() {
+        let mut board = self::create_board();
+
+        assert_eq!(
+            board.candidates((0, 0)),
+            &[
+                (0, Compass::East),
+                (1, Compass::East),
+                (2, Compass::East),
+                (3, Compass::East)
+            ]
+        );
+
+        board.put_piece(0, (0, 0), None).unwrap();
+        assert_eq!(
+            board.candidates((1, 0)),
+            &[
+                (4, Compass::East),
+                (6, Compass::East),
+                (8, Compass::East),
+                (10, Compass::East)
+            ]
+        );
+        assert_eq!(
+            board.candidates((0, 1)),
+            &[
+                (4, Compass::North),
+                (5, Compass::North),
+                (6, Compass::North),
+                (7, Compass::North)
+            ]
+        );
+
+        board.put_piece(4, (1, 0), None).unwrap();
+        board.put_piece(6, (0, 1), None).unwrap();
+        assert_eq!(
+            board.candidates((1, 1)),
+            &[
+                (13, Compass::South),
+                (14, Compass::North),
+                (15, Compass::North)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_cells() {
+        let mut board = self::create_board();
+        board.put_piece(0, (0, 0), None).unwrap();
+
+        let cells: Vec<_> = board.iter_cells(&ScanOrder::ColumnMajor).collect();
+        assert_eq!(cells.len(), 16);
+        assert_eq!(cells[0], ((0, 0), &board.cells[0][0]));
+        assert_eq!(cells[1], ((0, 1), &board.cells[1][0]));
+        assert_eq!(cells[4].0, (1, 0));
+    }
+
+    #[test]
+    fn test_compass_letters() {
+        for compass in &Compass::all() {
+            assert_eq!(Compass::from_letter(compass.letter()), Some(*compass));
+        }
+        assert_eq!(Compass::from_letter("n"), None);
+    }
+
     #[test]
     fn test_rotate_piece() {
         let mut board = self::create_board();
@@ -1,7 +1,11 @@
+use std::error::Error;
+use std::fmt;
+
 use board::Compass;
-use piece::{Props, Sides};
+use piece::{Piece, Props, Sides};
 
-#[derive(Debug, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Cell {
     CornerCell(Option<Props>, (Border, Border)),
     BorderCell(Option<Props>, Border),
@@ -9,29 +13,79 @@ pub enum Cell {
 }
 
 impl Cell {
-    // todo: test
+    /// Creates an empty cell of the given kind
+    pub fn empty(kind: CellKind) -> Self {
+        match kind {
+            CellKind::Corner(a, b) => Cell::CornerCell(None, (a, b)),
+            CellKind::Border(border) => Cell::BorderCell(None, border),
+            CellKind::Full => Cell::FullCell(None, None),
+        }
+    }
+
+    /// Returns the props of the placed piece, if any
+    pub fn get_props(&self) -> Option<Props> {
+        match self {
+            Cell::CornerCell(props, _) | Cell::BorderCell(props, _) | Cell::FullCell(props, _) => {
+                *props
+            }
+        }
+    }
+
+    /// Returns the kind of the cell, regardless of its content
+    pub fn get_kind(&self) -> CellKind {
+        match self {
+            Cell::CornerCell(_, (a, b)) => CellKind::Corner(*a, *b),
+            Cell::BorderCell(_, border) => CellKind::Border(*border),
+            Cell::FullCell(..) => CellKind::Full,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.get_props().is_none()
+    }
+
+    /// Checks if the piece has the same type as the cell
+    pub fn accepts(&self, piece: &Piece) -> bool {
+        matches!(
+            (self, piece),
+            (Cell::CornerCell(..), Piece::CornerPiece(_))
+                | (Cell::BorderCell(..), Piece::BorderPiece(_))
+                | (Cell::FullCell(..), Piece::FullPiece(_))
+        )
+    }
+
     pub fn get_compass(&self) -> Option<Compass> {
+        self.try_get_compass().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Returns the orientation of the piece, `None` for an empty full cell.
+    ///
+    /// Corner and border cells always have the orientation which turns the piece to the frame.
+    pub fn try_get_compass(&self) -> Result<Option<Compass>, FaceError> {
         match self {
-            Cell::CornerCell(_, borders) => Some(Cell::get_corner_offset(borders)),
-            Cell::BorderCell(_, border) => Some(Cell::get_border_offset(border)),
-            Cell::FullCell(Some(_), Some(compass)) => Some(compass.clone()),
-            Cell::FullCell(None, None) => None,
-            _ => panic!("cannot retrieve compass"),
+            Cell::CornerCell(_, borders) => Cell::try_get_corner_offset(borders).map(Some),
+            Cell::BorderCell(_, border) => Ok(Some(Cell::get_border_offset(border))),
+            Cell::FullCell(Some(_), Some(compass)) => Ok(Some(*compass)),
+            Cell::FullCell(None, None) => Ok(None),
+            Cell::FullCell(Some(_), None) => Err(FaceError::MissingCompass),
+            Cell::FullCell(None, Some(_)) => Err(FaceError::CompassWithoutPiece),
         }
     }
 
-    // todo: test
     pub fn get_corner_offset(borders: &(Border, Border)) -> Compass {
+        Cell::try_get_corner_offset(borders).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_corner_offset(borders: &(Border, Border)) -> Result<Compass, FaceError> {
         match borders {
-            (Border::North, Border::West) => Compass::East,
-            (Border::North, Border::East) => Compass::South,
-            (Border::South, Border::East) => Compass::West,
-            (Border::South, Border::West) => Compass::North,
-            _ => panic!("Not correct borders for corner"),
+            (Border::North, Border::West) => Ok(Compass::East),
+            (Border::North, Border::East) => Ok(Compass::South),
+            (Border::South, Border::East) => Ok(Compass::West),
+            (Border::South, Border::West) => Ok(Compass::North),
+            _ => Err(FaceError::InvalidCorner),
         }
     }
 
-    // todo: test
     pub fn get_border_offset(border: &Border) -> Compass {
         match border {
             Border::North => Compass::East,
@@ -141,6 +195,13 @@ impl Cell {
     }
 
     pub fn get_face(&self, side: Border) -> Face {
+        self.try_get_face(side).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Returns the face shown on the given side, `Face::None` if the cell is empty.
+    ///
+    /// `Err` if the content of the cell is inconsistent.
+    pub fn try_get_face(&self, side: Border) -> Result<Face, FaceError> {
         match self {
             Cell::CornerCell(props, borders) => Cell::get_face_corner(side, props, borders),
             Cell::BorderCell(props, border) => Cell::get_face_border(side, props, border),
@@ -148,58 +209,152 @@ impl Cell {
         }
     }
 
-    fn get_face_corner(side: Border, props: &Option<Props>, borders: &(Border, Border)) -> Face {
-        let offset = Sides::get_corner_offset(borders);
+    fn get_face_corner(
+        side: Border,
+        props: &Option<Props>,
+        borders: &(Border, Border),
+    ) -> Result<Face, FaceError> {
+        let offset = Cell::try_get_corner_offset(borders)?;
 
         if side == borders.0 || side == borders.1 {
-            return Face::Border;
+            return Ok(Face::Border);
         }
 
         match props {
             Some(Props {
                 kind: Sides::Corner(a, b),
                 ..
-            }) => Sides::get_face_corner(side, &offset, a, b),
-            None => Face::None,
-            _ => unreachable!(),
+            }) => Ok(Sides::get_face_corner(side, &offset, a, b)),
+            None => Ok(Face::None),
+            _ => Err(FaceError::KindMismatch),
         }
     }
 
-    fn get_face_border(side: Border, props: &Option<Props>, border: &Border) -> Face {
+    fn get_face_border(
+        side: Border,
+        props: &Option<Props>,
+        border: &Border,
+    ) -> Result<Face, FaceError> {
         let offset = Sides::get_border_offset(border);
 
         if side == *border {
-            return Face::Border;
+            return Ok(Face::Border);
         }
 
         match props {
             Some(Props {
                 kind: Sides::Border(a, b, c),
                 ..
-            }) => Sides::get_face_border(side, &offset, a, b, c),
-            None => Face::None,
-            _ => unreachable!(),
+            }) => Ok(Sides::get_face_border(side, &offset, a, b, c)),
+            None => Ok(Face::None),
+            _ => Err(FaceError::KindMismatch),
         }
     }
 
-    fn get_face_full(side: Border, props: &Option<Props>, compass: &Option<Compass>) -> Face {
+    fn get_face_full(
+        side: Border,
+        props: &Option<Props>,
+        compass: &Option<Compass>,
+    ) -> Result<Face, FaceError> {
         match compass {
             Some(compass) => match props {
                 Some(Props {
                     kind: Sides::Full(a, b, c, d),
                     ..
-                }) => Sides::get_face_full(side, compass, a, b, c, d),
-                _ => panic!("this full cell does not contain the same type of piece"),
+                }) => Ok(Sides::get_face_full(side, compass, a, b, c, d)),
+                Some(_) => Err(FaceError::KindMismatch),
+                None => Err(FaceError::CompassWithoutPiece),
             },
             None => match props {
-                None => Face::None,
-                _ => panic!("this cell cannot have a compass without a piece"),
+                None => Ok(Face::None),
+                _ => Err(FaceError::MissingCompass),
             },
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// Inconsistent content of a cell, or of a piece
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FaceError {
+    /// The piece does not have the same kind as the cell, or as its sides
+    KindMismatch,
+    /// A full piece is placed without compass
+    MissingCompass,
+    /// An empty full cell has a compass
+    CompassWithoutPiece,
+    /// The borders of a corner cell are not adjacent
+    InvalidCorner,
+}
+
+impl fmt::Display for FaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FaceError::KindMismatch => {
+                write!(f, "this cell does not contain the same type of piece")
+            }
+            FaceError::MissingCompass => write!(f, "this full piece doesn't have a compass"),
+            FaceError::CompassWithoutPiece => {
+                write!(f, "this cell cannot have a compass without a piece")
+            }
+            FaceError::InvalidCorner => write!(f, "Not correct borders for corner"),
+        }
+    }
+}
+
+impl Error for FaceError {}
+
+/// Shape of a cell, with the sides facing the frame of the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CellKind {
+    Corner(Border, Border),
+    Border(Border),
+    Full,
+}
+
+impl fmt::Display for CellKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CellKind::Corner(..) => write!(f, "corner"),
+            CellKind::Border(_) => write!(f, "border"),
+            CellKind::Full => write!(f, "full"),
+        }
+    }
+}
+
+impl CellKind {
+    /// Returns the kind of the cell at the given position of a board of the given dimensions
+    pub fn at(pos: (u16, u16), width: u16, height: u16) -> Self {
+        let (x, y) = pos;
+        let (last_x, last_y) = (width - 1, height - 1);
+        let north_south = match y {
+            0 => Some(Border::North),
+            y if y == last_y => Some(Border::South),
+            _ => None,
+        };
+        let west_east = match x {
+            0 => Some(Border::West),
+            x if x == last_x => Some(Border::East),
+            _ => None,
+        };
+
+        match (north_south, west_east) {
+            (Some(a), Some(b)) => CellKind::Corner(a, b),
+            (Some(border), None) | (None, Some(border)) => CellKind::Border(border),
+            (None, None) => CellKind::Full,
+        }
+    }
+
+    /// Returns the orientation forced on the pieces of this kind of cell
+    pub fn get_compass(&self) -> Option<Compass> {
+        match self {
+            CellKind::Corner(a, b) => Some(Cell::get_corner_offset(&(*a, *b))),
+            CellKind::Border(border) => Some(Cell::get_border_offset(border)),
+            CellKind::Full => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Border {
     North,
     East,
@@ -207,11 +362,11 @@ pub enum Border {
     West,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Face {
     Border,
     None,
-    Color(u8),
+    Color(u16),
 }
 
 #[cfg(test)]
@@ -481,7 +636,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "internal error: entered unreachable code")]
+    #[should_panic(expected = "this cell does not contain the same type of piece")]
     fn test_get_face_corner_piece() {
         let corner_cell = Cell::CornerCell(
             Some(Props {
@@ -507,7 +662,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "internal error: entered unreachable code")]
+    #[should_panic(expected = "this cell does not contain the same type of piece")]
     fn test_get_face_border_piece() {
         let border_cell = Cell::BorderCell(
             Some(Props {
@@ -520,7 +675,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "this full cell does not contain the same type of piece")]
+    #[should_panic(expected = "this cell does not contain the same type of piece")]
     fn test_get_face_full_piece_panic() {
         let full_cell = Cell::FullCell(
             Some(Props {
@@ -533,7 +688,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "this cell cannot have a compass without a piece")]
+    #[should_panic(expected = "this full piece doesn't have a compass")]
     fn test_get_face_full_compass_missing_panic() {
         let full_cell = Cell::FullCell(
             Some(Props {
@@ -546,9 +701,81 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "this full cell does not contain the same type of piece")]
+    #[should_panic(expected = "this cell cannot have a compass without a piece")]
     fn test_get_face_full_no_piece_compass_panic() {
         let full_cell = Cell::FullCell(None, Some(Compass::North));
         full_cell.get_face(South);
     }
+
+    #[test]
+    fn test_try_get_face() {
+        let props = Some(Props {
+            id: 12,
+            kind: Sides::Full(1, 2, 3, 4),
+        });
+
+        assert_eq!(
+            Cell::FullCell(props, Some(Compass::East)).try_get_face(North),
+            Ok(Face::Color(4))
+        );
+        assert_eq!(
+            Cell::FullCell(props, None).try_get_face(North),
+            Err(FaceError::MissingCompass)
+        );
+        assert_eq!(
+            Cell::BorderCell(props, West).try_get_face(North),
+            Err(FaceError::KindMismatch)
+        );
+        assert_eq!(
+            Cell::CornerCell(None, (North, South)).try_get_face(East),
+            Err(FaceError::InvalidCorner)
+        );
+    }
+
+    #[test]
+    fn test_try_get_compass() {
+        let props = Some(Props {
+            id: 12,
+            kind: Sides::Full(1, 2, 3, 4),
+        });
+
+        assert_eq!(
+            Cell::CornerCell(None, (South, West)).try_get_compass(),
+            Ok(Some(Compass::North))
+        );
+        assert_eq!(
+            Cell::BorderCell(None, East).try_get_compass(),
+            Ok(Some(Compass::South))
+        );
+        assert_eq!(Cell::FullCell(None, None).try_get_compass(), Ok(None));
+        assert_eq!(
+            Cell::FullCell(props, Some(Compass::West)).try_get_compass(),
+            Ok(Some(Compass::West))
+        );
+        assert_eq!(
+            Cell::FullCell(props, None).try_get_compass(),
+            Err(FaceError::MissingCompass)
+        );
+        assert_eq!(
+            Cell::FullCell(None, Some(Compass::West)).try_get_compass(),
+            Err(FaceError::CompassWithoutPiece)
+        );
+        assert_eq!(
+            Cell::CornerCell(None, (East, West)).try_get_compass(),
+            Err(FaceError::InvalidCorner)
+        );
+    }
+
+    #[test]
+    fn test_cell_kind_at() {
+        assert_eq!(CellKind::at((0, 0), 4, 3), CellKind::Corner(North, West));
+        assert_eq!(CellKind::at((3, 2), 4, 3), CellKind::Corner(South, East));
+        assert_eq!(CellKind::at((1, 0), 4, 3), CellKind::Border(North));
+        assert_eq!(CellKind::at((3, 1), 4, 3), CellKind::Border(East));
+        assert_eq!(CellKind::at((2, 1), 4, 3), CellKind::Full);
+        assert_eq!(
+            Cell::empty(CellKind::at((0, 1), 4, 3)),
+            Cell::BorderCell(None, West)
+        );
+    }
 }
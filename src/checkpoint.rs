@@ -0,0 +1,485 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use board::{BoardGame, Compass};
+use cell::Face;
+use solver::{SearchState, SearchStatus, Solver};
+
+/// Version of the checkpoint format written by `Checkpoint::write`
+const VERSION: u32 = 1;
+
+/// Text file holding the position of a `Solver`.
+///
+/// ```text
+/// eternity-checkpoint 1
+/// puzzle 9c2f04a1d3e87b65
+/// nodes 1234
+/// started 1
+/// propagate 0
+/// mrv 0
+/// pinned 0 0,0
+/// order 0,0 1,0 2,0 3,0
+/// frame 0 0 2 0 E
+/// frame 1 0 1 -
+/// ```
+///
+/// The `puzzle` line is the fingerprint of the board, see `BoardGame::fingerprint`. `propagate`
+/// tells if the search keeps domains, see `Solver::propagate`, and `mrv` if it fills the most
+/// constrained cell first, see `Solver::most_constrained`. `pinned` is the corner piece of
+/// grouped rotations and its cells, `-` when rotations are not grouped. Each `frame` line is a
+/// depth of the search: its cell, the index of the next candidate to try, and the placed piece
+/// with its orientation, `-` when the cell is still empty.
+pub struct Checkpoint;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckpointError {
+    UnsupportedVersion(String),
+    /// The checkpoint was written for other pieces
+    PuzzleMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// The line does not hold what the format expects at this place
+    Invalid {
+        line: usize,
+        expected: &'static str,
+    },
+    /// The search cannot be replayed on the board, see `Solver::from_state`
+    Replay,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckpointError::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint version {}", version)
+            }
+            CheckpointError::PuzzleMismatch { expected, found } => write!(
+                f,
+                "checkpoint of puzzle {:016x}, the pieces are those of puzzle {:016x}",
+                found, expected
+            ),
+            CheckpointError::Invalid { line, expected } => {
+                write!(f, "line {}: expected {}", line, expected)
+            }
+            CheckpointError::Replay => {
+                write!(f, "the search cannot be resumed on the cells of this board")
+            }
+        }
+    }
+}
+
+impl Error for CheckpointError {}
+
+impl Checkpoint {
+    /// Writes the position of the search
+    pub fn write(solver: &Solver) -> String {
+        let state = solver.state();
+        let positions = |positions: &[(u16, u16)]| {
+            positions
+                .iter()
+                .map(|(x, y)| format!(" {},{}", x, y))
+                .collect::<String>()
+        };
+
+        let mut content = format!(
+            "eternity-checkpoint {}\npuzzle {:016x}\nnodes {}\nstarted {}\npropagate {}\nmrv {}\n",
+            VERSION,
+            solver.board().fingerprint(),
+            state.nodes,
+            state.started as u8,
+            state.propagate as u8,
+            state.mrv as u8
+        );
+        match &state.pinned {
+            Some((piece, cells)) => content += &format!("pinned {}{}\n", piece, positions(cells)),
+            None => content += "pinned -\n",
+        }
+        content += &format!("order{}\n", positions(&state.order));
+        for (&(x, y), (next, placed)) in state.order.iter().zip(&state.frames) {
+            content += &match placed {
+                Some((piece, compass)) => {
+                    format!(
+                        "frame {} {} {} {} {}\n",
+                        x,
+                        y,
+                        next,
+                        piece,
+                        compass.letter()
+                    )
+                }
+                None => format!("frame {} {} {} -\n", x, y, next),
+            };
+        }
+        content
+    }
+
+    /// Resumes a search from a checkpoint, on the board the search started from
+    pub fn read(board: BoardGame, content: &str) -> Result<Solver, CheckpointError> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split_whitespace().collect::<Vec<_>>()))
+            .filter(|(_, tokens)| !tokens.is_empty());
+        let mut field = |key: &'static str| match lines.next() {
+            Some((number, ref tokens)) if tokens[0] == key => Ok((number, tokens[1..].to_vec())),
+            Some((number, _)) => Err(CheckpointError::Invalid {
+                line: number,
+                expected: key,
+            }),
+            None => Err(CheckpointError::Invalid {
+                line: content.lines().count() + 1,
+                expected: key,
+            }),
+        };
+
+        let (_, version) = field("eternity-checkpoint")?;
+        if version != [VERSION.to_string()] {
+            return Err(CheckpointError::UnsupportedVersion(version.join(" ")));
+        }
+
+        let (line, puzzle) = field("puzzle")?;
+        let found = match puzzle.as_slice() {
+            [hash] => u64::from_str_radix(hash, 16).ok(),
+            _ => None,
+        }
+        .ok_or(CheckpointError::Invalid {
+            line,
+            expected: "puzzle fingerprint",
+        })?;
+        let expected = board.fingerprint();
+        if found != expected {
+            return Err(CheckpointError::PuzzleMismatch { expected, found });
+        }
+
+        let (line, nodes) = field("nodes")?;
+        let nodes = single(&nodes).ok_or(CheckpointError::Invalid {
+            line,
+            expected: "node count",
+        })?;
+
+        let mut flag = |key: &'static str| {
+            let (line, value) = field(key)?;
+            match value.as_slice() {
+                ["0"] => Ok(false),
+                ["1"] => Ok(true),
+                _ => Err(CheckpointError::Invalid {
+                    line,
+                    expected: "0 or 1",
+                }),
+            }
+        };
+        let started = flag("started")?;
+        let propagate = flag("propagate")?;
+        let mrv = flag("mrv")?;
+
+        let (line, pinned) = field("pinned")?;
+        let invalid = CheckpointError::Invalid {
+            line,
+            expected: "pinned piece and cells",
+        };
+        let pinned = match pinned.as_slice() {
+            ["-"] => None,
+            [piece, cells @ ..] => match (piece.parse().ok(), positions(cells)) {
+                (Some(piece), Some(cells)) => Some((piece, cells)),
+                _ => return Err(invalid),
+            },
+            [] => return Err(invalid),
+        };
+
+        let (line, order) = field("order")?;
+        let order = positions(&order).ok_or(CheckpointError::Invalid {
+            line,
+            expected: "x,y positions",
+        })?;
+
+        let mut frames = Vec::new();
+        for (line, tokens) in lines {
+            let invalid = CheckpointError::Invalid {
+                line,
+                expected: "frame x y next piece compass",
+            };
+            let (x, y, next, placed) = match tokens.as_slice() {
+                ["frame", x, y, next, "-"] => (x, y, next, None),
+                ["frame", x, y, next, piece, compass] => {
+                    match (piece.parse().ok(), Compass::from_letter(compass)) {
+                        (Some(piece), Some(compass)) => (x, y, next, Some((piece, compass))),
+                        _ => return Err(invalid),
+                    }
+                }
+                _ => return Err(invalid),
+            };
+            match (x.parse::<u16>(), y.parse::<u16>(), next.parse()) {
+                (Ok(x), Ok(y), Ok(next)) if order.get(frames.len()) == Some(&(x, y)) => {
+                    frames.push((next, placed))
+                }
+                _ => return Err(invalid),
+            }
+        }
+
+        let state = SearchState {
+            order,
+            pinned,
+            started,
+            frames,
+            nodes,
+            propagate,
+            mrv,
+        };
+        Solver::from_state(board, state).ok_or(CheckpointError::Replay)
+    }
+
+    /// Writes the checkpoint to a file, replacing it at once so that a killed process never
+    /// leaves half a checkpoint.
+    ///
+    /// The checkpoint is first written next to the file, its name followed by `.tmp`.
+    pub fn save(solver: &Solver, path: &Path) -> io::Result<()> {
+        let mut name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?
+            .to_os_string();
+        name.push(".tmp");
+        let temporary = path.with_file_name(name);
+        fs::write(&temporary, Checkpoint::write(solver))?;
+        fs::rename(&temporary, path)
+    }
+
+    /// Continues the search, saving a checkpoint every `interval` placements and when it returns
+    pub fn search(solver: &mut Solver, path: &Path, interval: u64) -> io::Result<SearchStatus> {
+        loop {
+            let status = solver.search(interval.max(1));
+            Checkpoint::save(solver, path)?;
+            if status != SearchStatus::Paused {
+                return Ok(status);
+            }
+        }
+    }
+}
+
+/// Parses a single number
+fn single(tokens: &[&str]) -> Option<u64> {
+    match tokens {
+        [number] => number.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parses `x,y` tokens
+fn positions(tokens: &[&str]) -> Option<Vec<(u16, u16)>> {
+    tokens
+        .iter()
+        .map(|token| {
+            let mut coordinates = token.splitn(2, ',');
+            match (coordinates.next(), coordinates.next()) {
+                (Some(x), Some(y)) => Some((x.parse().ok()?, y.parse().ok()?)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+impl BoardGame {
+    /// Returns a hash of the dimensions, the pieces and the hints of the board (64 bit FNV-1a).
+    ///
+    /// The pieces placed by a search do not change it.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut feed = |value: u16| {
+            for byte in &value.to_le_bytes() {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+
+        feed(self.width);
+        feed(self.height);
+        for piece in &self.pieces {
+            let (a, b, c, d) = piece.get_faces(&Compass::North);
+            for face in &[a, b, c, d] {
+                feed(match face {
+                    Face::Color(color) => *color,
+                    _ => 0,
+                });
+            }
+        }
+        for hint in &self.hints {
+            feed(hint.piece);
+            feed(hint.pos.0);
+            feed(hint.pos.1);
+            feed(hint.compass as u16);
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::create_board;
+    use std::env;
+
+    #[test]
+    fn test_fingerprint() {
+        let board = self::create_board();
+        let mut placed = board.clone();
+        placed.put_piece(0, (0, 0), None).unwrap();
+
+        assert_eq!(board.fingerprint(), placed.fingerprint());
+
+        let content = include_str!("../pieces_4x4.txt").replacen("3 3 5 5", "3 3 5 4", 1);
+        let other = BoardGame::new(content.lines().map(|line| line.to_string()).collect());
+        assert_ne!(board.fingerprint(), other.fingerprint());
+
+        let hinted = |hint: &str| {
+            let content = format!("{}\n{}\n", include_str!("../pieces_4x4.txt"), hint);
+            BoardGame::new(content.lines().map(|line| line.to_string()).collect()).fingerprint()
+        };
+        assert_ne!(board.fingerprint(), hinted("hint 12 1 1 S"));
+        assert_ne!(hinted("hint 12 1 1 S"), hinted("hint 12 1 1 E"));
+        assert_ne!(hinted("hint 12 1 1 S"), hinted("hint 12 2 1 S"));
+    }
+
+    #[test]
+    fn test_write_read() {
+        let board = self::create_board();
+        let all = board.count_solutions(true);
+
+        let mut solver = board.solutions(true);
+        assert_eq!(solver.search(40), SearchStatus::Paused);
+        let content = Checkpoint::write(&solver);
+        assert!(content.starts_with("eternity-checkpoint 1\npuzzle "));
+        assert!(content.contains("\npropagate 0\nmrv 0\n"));
+        assert!(content.contains("\npinned 0 0,0\n"));
+
+        let mut resumed = Checkpoint::read(board.clone(), &content).unwrap();
+        assert_eq!(resumed.state(), solver.state());
+        assert_eq!(Checkpoint::write(&resumed), content);
+        assert_eq!(resumed.count_solutions(), all);
+        solver.count_solutions();
+        assert_eq!(resumed.nodes(), solver.nodes());
+    }
+
+    #[test]
+    fn test_write_read_propagate() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        let mut solver = Solver::new(board.clone()).propagate();
+        assert_eq!(solver.search(20), SearchStatus::Paused);
+        let content = Checkpoint::write(&solver);
+        assert!(content.contains("\npropagate 1\n"));
+
+        let mut resumed = Checkpoint::read(board.clone(), &content).unwrap();
+        assert_eq!(resumed.state(), solver.state());
+        assert_eq!(resumed.count_solutions(), all);
+    }
+
+    #[test]
+    fn test_write_read_mrv() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        let mut solver = Solver::new(board.clone()).most_constrained();
+        assert_eq!(solver.search(25), SearchStatus::Paused);
+        let content = Checkpoint::write(&solver);
+        assert!(content.contains("\nmrv 1\n"));
+
+        let mut resumed = Checkpoint::read(board.clone(), &content).unwrap();
+        assert_eq!(resumed.state(), solver.state());
+        assert_eq!(resumed.count_solutions(), all);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let board = self::create_board();
+        let mut solver = Solver::new(board.clone());
+        solver.search(30);
+        let content = Checkpoint::write(&solver);
+
+        assert_eq!(
+            Checkpoint::read(
+                board.clone(),
+                &content.replacen("checkpoint 1", "checkpoint 2", 1)
+            )
+            .map(|_| ()),
+            Err(CheckpointError::UnsupportedVersion("2".to_string()))
+        );
+
+        let other = include_str!("../pieces_4x4.txt").replacen("3 3 5 5", "3 3 5 4", 1);
+        let other = BoardGame::new(other.lines().map(|line| line.to_string()).collect());
+        assert_eq!(
+            Checkpoint::read(other.clone(), &content).map(|_| ()),
+            Err(CheckpointError::PuzzleMismatch {
+                expected: other.fingerprint(),
+                found: board.fingerprint()
+            })
+        );
+
+        assert_eq!(
+            Checkpoint::read(board.clone(), &content.replacen("nodes", "node", 1)).map(|_| ()),
+            Err(CheckpointError::Invalid {
+                line: 3,
+                expected: "nodes"
+            })
+        );
+        assert_eq!(
+            Checkpoint::read(board.clone(), &format!("{}frame 0 0 1 -\n", content)).map(|_| ()),
+            Err(CheckpointError::Invalid {
+                line: content.lines().count() + 1,
+                expected: "frame x y next piece compass"
+            })
+        );
+
+        assert_eq!(
+            Checkpoint::read(board.clone(), &format!("{}end\n", content)).map(|_| ()),
+            Err(CheckpointError::Invalid {
+                line: content.lines().count() + 1,
+                expected: "frame x y next piece compass"
+            })
+        );
+
+        let mut filled = board.clone();
+        filled.put_piece(0, (0, 0), None).unwrap();
+        assert_eq!(
+            Checkpoint::read(filled, &content).map(|_| ()),
+            Err(CheckpointError::Replay)
+        );
+    }
+
+    #[test]
+    fn test_search_saves() {
+        let board = self::create_board();
+        let path = env::temp_dir().join(format!("eternity-checkpoint-{}.txt", std::process::id()));
+
+        let mut solver = Solver::new(board.clone());
+        assert_eq!(
+            Checkpoint::search(&mut solver, &path, 25).unwrap(),
+            SearchStatus::Solution
+        );
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(saved, Checkpoint::write(&solver));
+
+        let mut resumed = Checkpoint::read(board, &saved).unwrap();
+        assert_eq!(resumed.count_solutions(), solver.count_solutions());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_save_temporary_name() {
+        let solver = Solver::new(self::create_board());
+        let path = env::temp_dir().join(format!("eternity-checkpoint-{}.tmp", std::process::id()));
+
+        Checkpoint::save(&solver, &path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            Checkpoint::write(&solver)
+        );
+        assert!(!path.with_extension("tmp.tmp").exists());
+        fs::remove_file(&path).unwrap();
+
+        assert!(Checkpoint::save(&solver, Path::new("/")).is_err());
+    }
+}
@@ -0,0 +1,1051 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rust_eternity::{
+    generate, verify, BenchmarkFile, BoardGame, Dlx, GeneratorConfig, ParallelSolver, PieceKind,
+    Puzzle, PuzzleFile, ScanOrder, SearchStatus, Solution, Solver, SvgOptions,
+};
+
+/// Exit code of a successful command
+pub const SUCCESS: i32 = 0;
+/// Exit code when the puzzle has no solution, or the solution is not valid
+pub const UNSOLVABLE: i32 = 1;
+/// Exit code when the time limit is reached before the end of the search
+pub const TIMEOUT: i32 = 2;
+/// Exit code for bad arguments and unreadable files
+pub const INPUT_ERROR: i32 = 3;
+
+/// Placements tried between two checks of the time limit
+const SLICE: u64 = 100_000;
+
+/// Description of a subcommand, used both to read its arguments and to write its help
+struct Command {
+    name: &'static str,
+    summary: &'static str,
+    positionals: &'static [&'static str],
+    /// Options taking a value, with the name of the value and their help
+    options: &'static [(&'static str, &'static str, &'static str)],
+    /// Options without value, with their help
+    flags: &'static [(&'static str, &'static str)],
+    /// Runs the command, writing its result to the first writer and its messages to the second
+    run: fn(&Args, &mut dyn Write, &mut dyn Write) -> Result<i32, String>,
+}
+
+const FROM: (&str, &str, &str) = (
+    "from",
+    "FORMAT",
+    "format of the puzzle file: native (default) or benchmark",
+);
+const OUTPUT: (&str, &str, &str) = ("output", "FILE", "writes to FILE instead of the terminal");
+const ORDER: (&str, &str, &str) = (
+    "order",
+    "ORDER",
+    "order of the cells: row-major (default), column-major, spiral, border-first or diagonal",
+);
+const PROPAGATE: (&str, &str) = (
+    "propagate",
+    "prunes the search with arc consistency on the domains of the cells",
+);
+const MRV: (&str, &str) = (
+    "mrv",
+    "fills next the cell with the fewest fitting pieces instead of following the order",
+);
+const TIME_LIMIT: (&str, &str, &str) = (
+    "time-limit",
+    "SECONDS",
+    "gives up after this time, exiting with code 2",
+);
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "solve",
+        summary: "searches a solution of a puzzle",
+        positionals: &["PUZZLE"],
+        options: &[
+            FROM,
+            ORDER,
+            TIME_LIMIT,
+            ("threads", "N", "searches on N threads, 1 by default"),
+            (
+                "format",
+                "FORMAT",
+                "format of the solution: text (default), ansi, svg or solution",
+            ),
+            OUTPUT,
+        ],
+        flags: &[PROPAGATE, MRV],
+        run: solve,
+    },
+    Command {
+        name: "verify",
+        summary: "checks a solution file against its puzzle",
+        positionals: &["PUZZLE", "SOLUTION"],
+        options: &[FROM],
+        flags: &[],
+        run: verify_solution,
+    },
+    Command {
+        name: "render",
+        summary: "draws a puzzle, empty or filled with a solution",
+        positionals: &["PUZZLE"],
+        options: &[
+            FROM,
+            ("solution", "FILE", "places the pieces of the solution file"),
+            (
+                "format",
+                "FORMAT",
+                "format of the drawing: text (default), ansi or svg",
+            ),
+            (
+                "cell-size",
+                "PIXELS",
+                "side of a cell in svg, 40 by default",
+            ),
+            OUTPUT,
+        ],
+        flags: &[
+            ("ids", "writes the id of each piece, in svg"),
+            ("rotations", "writes the rotation of each piece, in svg"),
+            ("mismatches", "outlines the mismatching sides, in svg"),
+        ],
+        run: render,
+    },
+    Command {
+        name: "generate",
+        summary: "creates a random puzzle with a known solution",
+        positionals: &[],
+        options: &[
+            ("size", "WxH", "dimensions of the board, 4x4 by default"),
+            (
+                "border-colors",
+                "N",
+                "colors between the pieces of the frame, 5 by default",
+            ),
+            (
+                "inner-colors",
+                "N",
+                "colors of the other edges, 17 by default",
+            ),
+            ("seed", "N", "seed of the random generator, 0 by default"),
+            ("solution", "FILE", "writes the solution to FILE"),
+            OUTPUT,
+        ],
+        flags: &[],
+        run: generate_puzzle,
+    },
+    Command {
+        name: "stats",
+        summary: "describes the pieces and colors of a puzzle",
+        positionals: &["PUZZLE"],
+        options: &[FROM],
+        flags: &[],
+        run: stats,
+    },
+    Command {
+        name: "convert",
+        summary: "writes a puzzle in the native format",
+        positionals: &["PUZZLE"],
+        options: &[FROM, OUTPUT],
+        flags: &[],
+        run: convert,
+    },
+    Command {
+        name: "bench",
+        summary: "times the search of a puzzle",
+        positionals: &["PUZZLE"],
+        options: &[
+            FROM,
+            (
+                "order",
+                "ORDERS",
+                "comma separated orders of the cells, or all, row-major by default",
+            ),
+            TIME_LIMIT,
+            ("runs", "N", "times each search N times, 1 by default"),
+        ],
+        flags: &[
+            PROPAGATE,
+            MRV,
+            (
+                "dlx",
+                "also times the exact cover search with dancing links, without order",
+            ),
+        ],
+        run: bench,
+    },
+];
+
+/// Arguments given to a subcommand
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    positionals: Vec<String>,
+    options: HashMap<&'static str, String>,
+    flags: HashSet<&'static str>,
+}
+
+impl Args {
+    /// Reads the arguments following the name of the command.
+    ///
+    /// Options are given as `--name value` or `--name=value`. Returns `Ok(None)` when the help is
+    /// asked for.
+    fn parse(command: &Command, args: &[String]) -> Result<Option<Args>, String> {
+        let mut parsed = Args::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "-h" || arg == "--help" {
+                return Ok(None);
+            }
+            if !arg.starts_with("--") || arg == "--" {
+                parsed.positionals.push(arg.clone());
+                continue;
+            }
+
+            let (name, inline) = match arg[2..].find('=') {
+                Some(i) => (&arg[2..2 + i], Some(arg[3 + i..].to_string())),
+                None => (&arg[2..], None),
+            };
+            if let Some(&(option, value, _)) = command.options.iter().find(|o| o.0 == name) {
+                let given = match inline.or_else(|| args.next().cloned()) {
+                    Some(given) => given,
+                    None => return Err(format!("missing {} after `--{}`", value, option)),
+                };
+                parsed.options.insert(option, given);
+            } else if let Some(&(flag, _)) = command.flags.iter().find(|f| f.0 == name) {
+                if inline.is_some() {
+                    return Err(format!("`--{}` takes no value", flag));
+                }
+                parsed.flags.insert(flag);
+            } else {
+                return Err(format!("unknown option `{}`", arg));
+            }
+        }
+
+        if parsed.positionals.len() < command.positionals.len() {
+            return Err(format!(
+                "missing {}",
+                command.positionals[parsed.positionals.len()]
+            ));
+        }
+        if let Some(extra) = parsed.positionals.get(command.positionals.len()) {
+            return Err(format!("unexpected argument `{}`", extra));
+        }
+
+        Ok(Some(parsed))
+    }
+
+    /// Returns the value of the option, `None` if it is not given
+    fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        match self.options.get(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid value `{}` for `--{}`", value, name)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value of the option, or `default` if it is not given
+    fn get_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        self.get(name).map(|value| value.unwrap_or(default))
+    }
+
+    /// Returns the value of the option among the given names
+    fn choice(&self, name: &str, choices: &[&'static str]) -> Result<&'static str, String> {
+        match self.options.get(name) {
+            Some(value) => choices
+                .iter()
+                .find(|choice| *choice == value)
+                .cloned()
+                .ok_or_else(|| {
+                    format!(
+                        "invalid value `{}` for `--{}`, expected one of {}",
+                        value,
+                        name,
+                        choices.join(", ")
+                    )
+                }),
+            None => Ok(choices[0]),
+        }
+    }
+
+    fn time_limit(&self) -> Result<Option<Duration>, String> {
+        match self.get::<f64>("time-limit")? {
+            Some(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+                match Duration::try_from_secs_f64(seconds) {
+                    Ok(limit) if Instant::now().checked_add(limit).is_some() => Ok(Some(limit)),
+                    _ => Err("`--time-limit` is too long".to_string()),
+                }
+            }
+            Some(_) => Err("`--time-limit` must be a positive number of seconds".to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the puzzle file given as the first positional argument
+    fn puzzle(&self) -> Result<Puzzle, String> {
+        let path = &self.positionals[0];
+        let content = read(path)?;
+        let parsed = match self.choice("from", &["native", "benchmark"])? {
+            "native" => PuzzleFile::parse(&content),
+            _ => BenchmarkFile::parse(&content),
+        };
+        parsed.map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Writes to the `--output` file, or to `out`
+    fn write(&self, out: &mut dyn Write, content: &str) -> Result<(), String> {
+        match self.options.get("output") {
+            Some(path) => fs::write(path, content).map_err(|e| format!("{}: {}", path, e)),
+            None => out
+                .write_all(content.as_bytes())
+                .map_err(|e| format!("cannot write the output: {}", e)),
+        }
+    }
+}
+
+/// Runs the command line, without the name of the program, and returns the exit code
+pub fn run(args: &[String], out: &mut dyn Write, err: &mut dyn Write) -> i32 {
+    let (name, rest) = match args.split_first() {
+        Some((name, rest)) => (name.as_str(), rest),
+        None => {
+            let _ = write!(err, "{}", help());
+            return INPUT_ERROR;
+        }
+    };
+    let name = match (name, rest.first()) {
+        ("-h", None) | ("--help", None) | ("help", None) => {
+            let _ = write!(out, "{}", help());
+            return SUCCESS;
+        }
+        ("help", Some(command)) => {
+            return match COMMANDS.iter().find(|c| c.name == command) {
+                Some(command) => {
+                    let _ = write!(out, "{}", command_help(command));
+                    SUCCESS
+                }
+                None => {
+                    let _ = writeln!(err, "error: unknown command `{}`", command);
+                    INPUT_ERROR
+                }
+            };
+        }
+        (name, _) => name,
+    };
+
+    let command = match COMMANDS.iter().find(|c| c.name == name) {
+        Some(command) => command,
+        None => {
+            let _ = writeln!(err, "error: unknown command `{}`\n\n{}", name, help());
+            return INPUT_ERROR;
+        }
+    };
+    let result = match Args::parse(command, rest) {
+        Ok(Some(args)) => (command.run)(&args, out, err),
+        Ok(None) => {
+            let _ = write!(out, "{}", command_help(command));
+            return SUCCESS;
+        }
+        Err(e) => Err(format!(
+            "{}\n\nSee `rust-eternity {} --help`.",
+            e, command.name
+        )),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            let _ = writeln!(err, "error: {}", e);
+            INPUT_ERROR
+        }
+    }
+}
+
+/// Returns the help of the program
+fn help() -> String {
+    let mut help = "Solver and tools for edge matching puzzles like Eternity II\n\n\
+                    Usage: rust-eternity <COMMAND> [OPTIONS]\n\nCommands:\n"
+        .to_string();
+    for command in COMMANDS {
+        help += &format!("  {:<10}{}\n", command.name, command.summary);
+    }
+    help += "\nRun `rust-eternity <COMMAND> --help` for the options of a command.\n";
+    help += EXIT_CODES;
+    help
+}
+
+const EXIT_CODES: &str = "\nExit codes:\n  0  success\n  1  no solution, or invalid solution\n  \
+                          2  time limit reached\n  3  bad arguments or input files\n";
+
+/// Returns the help of a subcommand
+fn command_help(command: &Command) -> String {
+    let mut usage = format!("rust-eternity {} [OPTIONS]", command.name);
+    for positional in command.positionals {
+        usage += &format!(" <{}>", positional);
+    }
+
+    let mut lines: Vec<(String, &str)> = command
+        .options
+        .iter()
+        .map(|&(name, value, help)| (format!("--{} <{}>", name, value), help))
+        .collect();
+    lines.extend(
+        command
+            .flags
+            .iter()
+            .map(|&(name, help)| (format!("--{}", name), help)),
+    );
+    lines.push(("-h, --help".to_string(), "prints this help"));
+
+    let width = lines.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut help = format!(
+        "rust-eternity {}: {}\n\nUsage: {}\n\nOptions:\n",
+        command.name, command.summary, usage
+    );
+    for (name, text) in lines {
+        help += &format!("  {:<width$}  {}\n", name, text, width = width);
+    }
+    help += EXIT_CODES;
+    help
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn parse_order(name: &str) -> Result<ScanOrder, String> {
+    match name {
+        "row-major" => Ok(ScanOrder::RowMajor),
+        "column-major" => Ok(ScanOrder::ColumnMajor),
+        "spiral" => Ok(ScanOrder::Spiral),
+        "border-first" => Ok(ScanOrder::BorderFirst),
+        "diagonal" => Ok(ScanOrder::Diagonal),
+        _ => Err(format!(
+            "unknown order `{}`, expected row-major, column-major, spiral, border-first or \
+             diagonal",
+            name
+        )),
+    }
+}
+
+/// End of a search
+enum Outcome {
+    Solved(BoardGame),
+    Unsolvable,
+    Timeout,
+}
+
+/// Search that can be run by slices, the backtracking `Solver` or the exact cover `Dlx`
+trait Engine {
+    fn search(&mut self, budget: u64) -> SearchStatus;
+    fn nodes(&self) -> u64;
+    /// Returns the board of the search, complete after `SearchStatus::Solution`
+    fn board(&self) -> BoardGame;
+}
+
+impl Engine for Solver {
+    fn search(&mut self, budget: u64) -> SearchStatus {
+        Solver::search(self, budget)
+    }
+
+    fn nodes(&self) -> u64 {
+        Solver::nodes(self)
+    }
+
+    fn board(&self) -> BoardGame {
+        Solver::board(self).clone()
+    }
+}
+
+impl Engine for Dlx {
+    fn search(&mut self, budget: u64) -> SearchStatus {
+        Dlx::search(self, budget)
+    }
+
+    fn nodes(&self) -> u64 {
+        Dlx::nodes(self)
+    }
+
+    fn board(&self) -> BoardGame {
+        Dlx::board(self)
+    }
+}
+
+/// Searches the first solution on this thread, checking the time limit between slices of the
+/// search
+fn search<E: Engine>(mut solver: E, deadline: Option<Instant>) -> (Outcome, u64) {
+    loop {
+        match solver.search(SLICE) {
+            SearchStatus::Solution => {
+                return (Outcome::Solved(solver.board()), solver.nodes());
+            }
+            SearchStatus::Exhausted | SearchStatus::Stopped => {
+                return (Outcome::Unsolvable, solver.nodes())
+            }
+            SearchStatus::Paused => {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return (Outcome::Timeout, solver.nodes());
+                }
+            }
+        }
+    }
+}
+
+fn solve(args: &Args, out: &mut dyn Write, err: &mut dyn Write) -> Result<i32, String> {
+    let board = BoardGame::from_puzzle(args.puzzle()?);
+    let order = parse_order(args.options.get("order").map_or("row-major", |o| o))?;
+    let threads: usize = args.get_or("threads", 1)?;
+    let format = args.choice("format", &["text", "ansi", "svg", "solution"])?;
+    let limit = args.time_limit()?;
+    if threads == 0 {
+        return Err("`--threads` must be at least 1".to_string());
+    }
+
+    let mut solver = Solver::with_order(board, &order);
+    if args.flags.contains("propagate") {
+        solver = solver.propagate();
+    }
+    if args.flags.contains("mrv") {
+        solver = solver.most_constrained();
+    }
+    let outcome = if threads == 1 {
+        search(
+            solver,
+            limit.and_then(|limit| Instant::now().checked_add(limit)),
+        )
+        .0
+    } else {
+        let stop = Arc::new(AtomicBool::new(false));
+        let parallel = ParallelSolver::new(solver)
+            .threads(threads)
+            .stop_on(stop.clone());
+        let (sender, receiver) = mpsc::channel();
+        let search = thread::spawn(move || {
+            let _ = sender.send(parallel.solve());
+        });
+        let solution = match limit {
+            Some(limit) => receiver.recv_timeout(limit).map_err(|_| ()),
+            None => receiver.recv().map_err(|_| ()),
+        };
+        stop.store(true, Ordering::Relaxed);
+        search.join().map_err(|_| "the search failed".to_string())?;
+        match solution {
+            Ok(Some(board)) => Outcome::Solved(board),
+            Ok(None) => Outcome::Unsolvable,
+            Err(()) => Outcome::Timeout,
+        }
+    };
+
+    let board = match outcome {
+        Outcome::Solved(board) => board,
+        Outcome::Unsolvable => {
+            let _ = writeln!(err, "no solution");
+            return Ok(UNSOLVABLE);
+        }
+        Outcome::Timeout => {
+            let _ = writeln!(err, "time limit reached");
+            return Ok(TIMEOUT);
+        }
+    };
+    let content = match format {
+        "text" => board.render(false).to_string(),
+        "ansi" => board.render(true).to_string(),
+        "svg" => board.to_svg(&SvgOptions::default()),
+        _ => Solution::from_board(&board).to_string(),
+    };
+    args.write(out, &content)?;
+
+    Ok(SUCCESS)
+}
+
+fn verify_solution(args: &Args, out: &mut dyn Write, _err: &mut dyn Write) -> Result<i32, String> {
+    let puzzle = args.puzzle()?;
+    let path = &args.positionals[1];
+    let solution = Solution::parse(&read(path)?).map_err(|e| format!("{}: {}", path, e))?;
+
+    match verify(&puzzle, &solution) {
+        Ok(()) => {
+            writeln!(out, "valid solution").map_err(|e| e.to_string())?;
+            Ok(SUCCESS)
+        }
+        Err(problems) => {
+            for problem in problems {
+                writeln!(out, "{}", problem).map_err(|e| e.to_string())?;
+            }
+            Ok(UNSOLVABLE)
+        }
+    }
+}
+
+fn render(args: &Args, out: &mut dyn Write, _err: &mut dyn Write) -> Result<i32, String> {
+    let mut board = BoardGame::from_puzzle(args.puzzle()?);
+    let format = args.choice("format", &["text", "ansi", "svg"])?;
+    let cell_size = args.get_or("cell-size", SvgOptions::default().cell_size)?;
+
+    if let Some(path) = args.options.get("solution") {
+        let solution = Solution::parse(&read(path)?).map_err(|e| format!("{}: {}", path, e))?;
+        if (solution.width, solution.height) != (board.width, board.height) {
+            return Err(format!("{}: the solution does not fit the puzzle", path));
+        }
+        for y in 0..board.height {
+            for x in 0..board.width {
+                if let Some((piece, compass)) = solution.get((x, y)) {
+                    if board.is_locked((x, y)) {
+                        continue;
+                    }
+                    board
+                        .put_piece(piece, (x, y), Some(compass))
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                }
+            }
+        }
+    }
+
+    let content = match format {
+        "text" => board.render(false).to_string(),
+        "ansi" => board.render(true).to_string(),
+        _ => board.to_svg(&SvgOptions {
+            cell_size,
+            show_ids: args.flags.contains("ids"),
+            show_rotations: args.flags.contains("rotations"),
+            show_mismatches: args.flags.contains("mismatches"),
+            ..SvgOptions::default()
+        }),
+    };
+    args.write(out, &content)?;
+
+    Ok(SUCCESS)
+}
+
+fn generate_puzzle(args: &Args, out: &mut dyn Write, _err: &mut dyn Write) -> Result<i32, String> {
+    let size = args.options.get("size").map_or("4x4", |size| size);
+    let dimensions: Vec<_> = size.split('x').map(u16::from_str).collect();
+    let (width, height) = match dimensions[..] {
+        [Ok(width), Ok(height)] => (width, height),
+        _ => {
+            return Err(format!(
+                "invalid value `{}` for `--size`, expected WxH",
+                size
+            ))
+        }
+    };
+    let config = GeneratorConfig {
+        width,
+        height,
+        border_colors: args.get_or("border-colors", 5)?,
+        inner_colors: args.get_or("inner-colors", 17)?,
+        seed: args.get_or("seed", 0)?,
+    };
+
+    if width < 2 || height < 2 {
+        return Err("the board needs at least 2x2 cells".to_string());
+    }
+    if (width as usize) * (height as usize) > u16::MAX as usize + 1 {
+        return Err(format!("{}x{} is too many pieces", width, height));
+    }
+    if config.border_colors == 0 || config.inner_colors == 0 {
+        return Err("border and inner colors are needed".to_string());
+    }
+    if config
+        .border_colors
+        .checked_add(config.inner_colors)
+        .is_none()
+    {
+        return Err("too many colors".to_string());
+    }
+
+    let (puzzle, solution) = generate(&config);
+    if let Some(path) = args.options.get("solution") {
+        fs::write(path, solution.to_string()).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    args.write(out, &PuzzleFile::write(&puzzle))?;
+
+    Ok(SUCCESS)
+}
+
+fn stats(args: &Args, out: &mut dyn Write, _err: &mut dyn Write) -> Result<i32, String> {
+    let puzzle = args.puzzle()?;
+    let mut kinds = [0; 3];
+    // sides of each color, on the frame and inside
+    let mut sides = vec![(0, 0); puzzle.colors as usize + 1];
+
+    for piece in &puzzle.pieces {
+        let [a, b, c, d] = piece.get_props().kind.values();
+        let frame: &[u16] = match piece.get_kind() {
+            PieceKind::Corner => {
+                kinds[0] += 1;
+                &[c, d]
+            }
+            PieceKind::Border => {
+                kinds[1] += 1;
+                sides[c as usize].1 += 1;
+                &[b, d]
+            }
+            PieceKind::Full => {
+                kinds[2] += 1;
+                for &color in &[a, b, c, d] {
+                    sides[color as usize].1 += 1;
+                }
+                &[]
+            }
+        };
+        for &color in frame {
+            sides[color as usize].0 += 1;
+        }
+    }
+
+    let mut report = format!(
+        "size: {}x{}\npieces: {} ({} corners, {} borders, {} inner)\nhints: {}\ncolors: {}\n",
+        puzzle.width,
+        puzzle.height,
+        puzzle.pieces.len(),
+        kinds[0],
+        kinds[1],
+        kinds[2],
+        puzzle.hints.len(),
+        puzzle.colors
+    );
+    let used = |side: fn(&(u32, u32)) -> u32| {
+        sides
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, count)| side(count) > 0)
+            .count()
+    };
+    report += &format!(
+        "frame colors: {}\ninner colors: {}\n",
+        used(|count| count.0),
+        used(|count| count.1)
+    );
+
+    report += "\ncolor  frame  inner\n";
+    for (color, &(frame, inner)) in sides.iter().enumerate().skip(1) {
+        report += &format!("{:>5}  {:>5}  {:>5}\n", color, frame, inner);
+    }
+    let unpaired: Vec<_> = sides
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, &(frame, inner))| frame % 2 == 1 || inner % 2 == 1)
+        .map(|(color, _)| color.to_string())
+        .collect();
+    if !unpaired.is_empty() {
+        report += &format!(
+            "\nodd number of sides, the puzzle cannot be solved: colors {}\n",
+            unpaired.join(", ")
+        );
+    }
+    out.write_all(report.as_bytes())
+        .map_err(|e| format!("cannot write the output: {}", e))?;
+
+    Ok(SUCCESS)
+}
+
+fn convert(args: &Args, out: &mut dyn Write, _err: &mut dyn Write) -> Result<i32, String> {
+    let puzzle = args.puzzle()?;
+    args.write(out, &PuzzleFile::write(&puzzle))?;
+
+    Ok(SUCCESS)
+}
+
+fn bench(args: &Args, out: &mut dyn Write, _err: &mut dyn Write) -> Result<i32, String> {
+    let board = BoardGame::from_puzzle(args.puzzle()?);
+    let mut names: Vec<&str> = match args.options.get("order").map(|o| o.as_str()) {
+        None => vec!["row-major"],
+        Some("all") => vec![
+            "row-major",
+            "column-major",
+            "spiral",
+            "border-first",
+            "diagonal",
+        ],
+        Some(names) => names.split(',').collect(),
+    };
+    let mut orders = names
+        .iter()
+        .map(|name| parse_order(name).map(Some))
+        .collect::<Result<Vec<_>, _>>()?;
+    if args.flags.contains("dlx") {
+        names.push("dlx");
+        orders.push(None);
+    }
+    let runs: u32 = args.get_or("runs", 1)?;
+    let limit = args.time_limit()?;
+
+    let mut code = SUCCESS;
+    let mut report = format!(
+        "{:<14}{:>12}{:>14}{:>12}{:>14}\n",
+        "order", "result", "placements", "seconds", "placements/s"
+    );
+    for (name, order) in names.iter().zip(&orders) {
+        for _ in 0..runs.max(1) {
+            let start = Instant::now();
+            let deadline = limit.and_then(|limit| start.checked_add(limit));
+            let (outcome, nodes) = match order {
+                Some(order) => {
+                    let mut solver = Solver::with_order(board.clone(), order);
+                    if args.flags.contains("propagate") {
+                        solver = solver.propagate();
+                    }
+                    if args.flags.contains("mrv") {
+                        solver = solver.most_constrained();
+                    }
+                    search(solver, deadline)
+                }
+                None => search(Dlx::new(board.clone()), deadline),
+            };
+            let seconds = start.elapsed().as_secs_f64();
+
+            let result = match outcome {
+                Outcome::Solved(_) => "solved",
+                Outcome::Unsolvable => "unsolvable",
+                Outcome::Timeout => {
+                    code = TIMEOUT;
+                    "timeout"
+                }
+            };
+            report += &format!(
+                "{:<14}{:>12}{:>14}{:>12.3}{:>14.0}\n",
+                name,
+                result,
+                nodes,
+                seconds,
+                nodes as f64 / seconds.max(1e-9)
+            );
+        }
+    }
+    out.write_all(report.as_bytes())
+        .map_err(|e| format!("cannot write the output: {}", e))?;
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|arg| arg.to_string()).collect()
+    }
+
+    /// Runs the command line, returns the exit code and what was written
+    fn run_line(line: &str) -> (i32, String, String) {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let code = run(&self::args(line), &mut out, &mut err);
+
+        (
+            code,
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+        )
+    }
+
+    fn temp(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("eternity-cli-{}-{}", process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let command = &COMMANDS[0];
+        let args = Args::parse(command, &self::args("p.txt --order spiral --threads=4"))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(args.positionals, vec!["p.txt"]);
+        assert_eq!(args.options["order"], "spiral");
+        assert_eq!(args.get::<usize>("threads"), Ok(Some(4)));
+        assert_eq!(args.get_or::<u64>("time-limit", 7), Ok(7));
+        assert_eq!(Args::parse(command, &self::args("p.txt -h")), Ok(None));
+
+        for (line, error) in &[
+            ("", "missing PUZZLE"),
+            ("p.txt q.txt", "unexpected argument `q.txt`"),
+            ("p.txt --order", "missing ORDER after `--order`"),
+            ("p.txt --verbose", "unknown option `--verbose`"),
+        ] {
+            assert_eq!(
+                Args::parse(command, &self::args(line)),
+                Err(error.to_string())
+            );
+        }
+
+        let render = COMMANDS.iter().find(|c| c.name == "render").unwrap();
+        let args = Args::parse(render, &self::args("--ids p.txt"))
+            .unwrap()
+            .unwrap();
+        assert!(args.flags.contains("ids"));
+        assert_eq!(
+            Args::parse(render, &self::args("p.txt --ids=yes")),
+            Err("`--ids` takes no value".to_string())
+        );
+        assert!(args
+            .choice("format", &["text", "svg"])
+            .is_ok_and(|format| format == "text"));
+    }
+
+    #[test]
+    fn test_help() {
+        let (code, out, _) = self::run_line("--help");
+        assert_eq!(code, SUCCESS);
+        for command in COMMANDS {
+            assert!(out.contains(command.name));
+        }
+
+        let (code, out, _) = self::run_line("solve --help");
+        assert_eq!(code, SUCCESS);
+        assert!(out.contains("Usage: rust-eternity solve [OPTIONS] <PUZZLE>"));
+        assert!(out.contains("--time-limit <SECONDS>"));
+        assert_eq!(self::run_line("help solve").1, out);
+
+        let (code, _, err) = self::run_line("");
+        assert_eq!(code, INPUT_ERROR);
+        assert!(err.contains("Commands:"));
+    }
+
+    #[test]
+    fn test_input_errors() {
+        for line in &[
+            "unknown",
+            "solve",
+            "solve missing-file.txt",
+            "solve pieces_4x4.txt --format pdf",
+            "solve pieces_4x4.txt --order random",
+            "solve pieces_4x4.txt --from benchmark",
+            "generate --size 4",
+            "generate --size 1x4",
+            "verify pieces_4x4.txt",
+            "solve pieces_4x4.txt --time-limit -1",
+            "solve pieces_4x4.txt --time-limit 1e30",
+            "solve pieces_4x4.txt --time-limit 1.5e19",
+        ] {
+            let (code, _, err) = self::run_line(line);
+            assert_eq!(code, INPUT_ERROR, "{}", line);
+            assert!(err.starts_with("error: "), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_solve_verify() {
+        let solution = self::temp("solution.txt");
+        let (code, _, _) = self::run_line(&format!(
+            "solve pieces_4x4.txt --order spiral --format solution --output {}",
+            solution
+        ));
+        assert_eq!(code, SUCCESS);
+
+        let (code, out, _) = self::run_line(&format!("verify pieces_4x4.txt {}", solution));
+        assert_eq!((code, out.as_str()), (SUCCESS, "valid solution\n"));
+
+        let (code, out, _) =
+            self::run_line(&format!("render pieces_4x4.txt --solution {}", solution));
+        assert_eq!(code, SUCCESS);
+        assert!(!out.contains('.'));
+
+        let (code, out, _) = self::run_line("solve pieces_4x4.txt --propagate --mrv --threads 2");
+        assert_eq!(code, SUCCESS);
+        assert!(out.starts_with("+--##--+"));
+
+        fs::write(&solution, format!("4 4\n{}", "-\n".repeat(16))).unwrap();
+        let (code, out, _) = self::run_line(&format!("verify pieces_4x4.txt {}", solution));
+        assert_eq!(code, UNSOLVABLE);
+        assert!(!out.is_empty());
+        fs::remove_file(&solution).unwrap();
+    }
+
+    #[test]
+    fn test_solve_unsolvable_timeout() {
+        let puzzle = self::temp("unsolvable.txt");
+        fs::write(
+            &puzzle,
+            "2 2\n2\n1\n1 1 1 1\n0 0 1 1\n0 0 1 1\n0 0 1 1\n0 0 2 2\n",
+        )
+        .unwrap();
+        assert_eq!(self::run_line(&format!("solve {}", puzzle)).0, UNSOLVABLE);
+        fs::remove_file(&puzzle).unwrap();
+
+        let (puzzle, _) = generate(&GeneratorConfig {
+            width: 12,
+            height: 12,
+            border_colors: 5,
+            inner_colors: 17,
+            seed: 1,
+        });
+        let path = self::temp("large.txt");
+        fs::write(&path, PuzzleFile::write(&puzzle)).unwrap();
+        assert_eq!(
+            self::run_line(&format!("solve {} --time-limit 0", path)).0,
+            TIMEOUT
+        );
+        assert_eq!(
+            self::run_line(&format!("bench {} --time-limit 0", path)).0,
+            TIMEOUT
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_generate_convert_stats() {
+        let (puzzle, solution) = (self::temp("generated.txt"), self::temp("hidden.txt"));
+        let (code, out, _) = self::run_line(&format!(
+            "generate --size 5x3 --border-colors 2 --inner-colors 3 --seed 4 --solution {}",
+            solution
+        ));
+        assert_eq!(code, SUCCESS);
+        assert!(out.starts_with("5 3\n5\n"));
+        fs::write(&puzzle, &out).unwrap();
+        assert_eq!(
+            self::run_line(&format!("verify {} {}", puzzle, solution)).0,
+            SUCCESS
+        );
+
+        let (code, out, _) = self::run_line(&format!("stats {}", puzzle));
+        assert_eq!(code, SUCCESS);
+        assert!(out.contains("pieces: 15 (4 corners, 8 borders, 3 inner)"));
+        assert!(out.contains("frame colors: 2\n"));
+        assert!(!out.contains("odd number"));
+
+        let benchmark: String = PuzzleFile::parse(&fs::read_to_string(&puzzle).unwrap())
+            .unwrap()
+            .pieces
+            .iter()
+            .map(|piece| {
+                let [a, b, c, d] = piece.get_props().kind.values();
+                format!("{} {} {} {}\n", a, b, c, d)
+            })
+            .collect();
+        fs::write(&puzzle, benchmark).unwrap();
+        let (code, out, _) = self::run_line(&format!("convert {} --from benchmark", puzzle));
+        assert_eq!(code, SUCCESS);
+        assert!(out.starts_with("5 3\n5\n1\n1 1 1 1\n"));
+
+        fs::remove_file(&puzzle).unwrap();
+        fs::remove_file(&solution).unwrap();
+    }
+
+    #[test]
+    fn test_bench() {
+        let (code, out, _) = self::run_line("bench pieces_4x4.txt --order all --runs 2 --mrv");
+
+        assert_eq!(code, SUCCESS);
+        assert_eq!(out.lines().count(), 11);
+        assert_eq!(out.matches("solved").count(), 10);
+
+        let (code, out, _) = self::run_line("bench pieces_4x4.txt --dlx");
+        assert_eq!(code, SUCCESS);
+        assert_eq!(out.lines().count(), 3);
+        assert!(out.lines().nth(2).unwrap().starts_with("dlx "));
+        assert_eq!(out.matches("solved").count(), 2);
+    }
+}
@@ -0,0 +1,503 @@
+use board::{BoardGame, Compass};
+use cell::Face;
+use scan::ScanOrder;
+use solver::SearchStatus;
+
+/// Step of `Dlx::search` to run next
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    /// Chooses the item to cover at a new level, or reports a solution
+    Enter,
+    /// Tries the current option of the deepest level
+    Try,
+    /// Takes back the option of the deepest level and moves to the next one
+    Leave,
+    Done,
+}
+
+/// Exact cover search of the empty cells of a board, with Dancing Links.
+///
+/// The board is turned into a matrix of options, one per `(cell, piece, Compass)` placement
+/// allowed by the kind of the cell. Every cell and every piece is a primary item, covered by
+/// exactly one option. Every edge between two cells is a secondary item, and each option gives
+/// the edges of its cell the colors of the faces of its piece: two options sharing an edge must
+/// agree on its color. Solutions are then enumerated with Knuth's Algorithm C, Algorithm X with
+/// colors, always branching on the item with the fewest options left.
+///
+/// Pieces already on the board are kept, their cell only has their option.
+#[derive(Debug, Clone)]
+pub struct Dlx {
+    board: BoardGame,
+    /// Cell, piece and compass of each option
+    options: Vec<((u16, u16), u16, Compass)>,
+    /// Links of the items still to cover, `0` heads the primary items and the last index the
+    /// secondary items
+    llink: Vec<usize>,
+    rlink: Vec<usize>,
+    /// Item of each node, its number of options for the header of an item, and minus the option
+    /// that follows for a spacer
+    top: Vec<isize>,
+    ulink: Vec<usize>,
+    dlink: Vec<usize>,
+    /// Color given to a secondary item, `0` for primary items and `-1` once purified
+    color: Vec<i32>,
+    /// Node of the option tried at each level
+    choices: Vec<usize>,
+    step: Step,
+    nodes: u64,
+}
+
+impl Dlx {
+    /// Builds the matrix of the board
+    pub fn new(mut board: BoardGame) -> Self {
+        board.discard_history();
+        let (width, height) = (board.width as usize, board.height as usize);
+        let cells = width * height;
+        let primary = cells + board.pieces.len();
+        let horizontal = (width - 1) * height;
+        let items = primary + horizontal + width * (height - 1);
+
+        let mut options = Vec::new();
+        for (pos, cell) in board.iter_cells(&ScanOrder::RowMajor) {
+            match (cell.get_props(), cell.get_compass()) {
+                (Some(props), Some(compass)) => options.push((pos, props.id, compass)),
+                _ => {
+                    let compasses = match cell.get_kind().get_compass() {
+                        Some(compass) => vec![compass],
+                        None => Compass::all().to_vec(),
+                    };
+                    for piece in &board.pieces {
+                        let id = piece.get_props().id;
+                        if board.placed[id as usize] || !cell.accepts(piece) {
+                            continue;
+                        }
+                        for &compass in &compasses {
+                            options.push((pos, id, compass));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut dlx = Dlx {
+            board,
+            options: Vec::with_capacity(options.len()),
+            llink: (0..items + 2).map(|i| i.wrapping_sub(1)).collect(),
+            rlink: (0..items + 2).map(|i| i + 1).collect(),
+            top: vec![0; items + 2],
+            ulink: (0..items + 2).collect(),
+            dlink: (0..items + 2).collect(),
+            color: vec![0; items + 2],
+            choices: Vec::new(),
+            step: Step::Enter,
+            nodes: 0,
+        };
+        // two circular lists: the primary items after 0, the secondary items after `items + 1`
+        let head = items + 1;
+        dlx.llink[0] = primary;
+        dlx.rlink[primary] = 0;
+        dlx.llink[primary + 1] = head;
+        dlx.rlink[head] = primary + 1;
+        dlx.llink[head] = items;
+        dlx.rlink[items] = head;
+        if primary == items {
+            dlx.llink[head] = head;
+            dlx.rlink[head] = head;
+        }
+        // the first spacer takes the place of the head, which is not a node
+        dlx.top[head] = 0;
+
+        for (pos, id, compass) in options {
+            let (x, y) = (pos.0 as usize, pos.1 as usize);
+            let face = |face: Face| match face {
+                Face::Color(color) => color as i32 + 2,
+                _ => 1,
+            };
+            let (north, east, south, west) = dlx.board.pieces[id as usize].get_faces(&compass);
+
+            let mut row = vec![(1 + y * width + x, 0), (1 + cells + id as usize, 0)];
+            let vertical = primary + horizontal + 1;
+            if y > 0 {
+                row.push((vertical + (y - 1) * width + x, face(north)));
+            }
+            if x + 1 < width {
+                row.push((primary + 1 + y * (width - 1) + x, face(east)));
+            }
+            if y + 1 < height {
+                row.push((vertical + y * width + x, face(south)));
+            }
+            if x > 0 {
+                row.push((primary + 1 + y * (width - 1) + x - 1, face(west)));
+            }
+            dlx.add_option(&row);
+            dlx.options.push((pos, id, compass));
+        }
+
+        dlx
+    }
+
+    /// Returns the number of options of the matrix
+    pub fn options(&self) -> usize {
+        self.options.len()
+    }
+
+    /// Continues the search and returns the next solution, `None` once the search space is exhausted
+    pub fn next_solution(&mut self) -> Option<BoardGame> {
+        match self.search(u64::MAX) {
+            SearchStatus::Solution => Some(self.board()),
+            _ => None,
+        }
+    }
+
+    /// Counts the remaining solutions without building the solved boards
+    pub fn count_solutions(&mut self) -> usize {
+        let mut count = 0;
+        while self.search(u64::MAX) == SearchStatus::Solution {
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns the number of options tried so far
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Returns the board holding the options of the current search, complete after a solution
+    pub fn board(&self) -> BoardGame {
+        let mut board = self.board.clone();
+        for &node in &self.choices {
+            if node > self.items() {
+                let (pos, id, compass) = self.options[self.option(node)];
+                if !board.placed[id as usize] {
+                    board
+                        .set_piece(id, pos, Some(compass))
+                        .unwrap_or_else(|e| panic!("{}", e));
+                }
+            }
+        }
+        board
+    }
+
+    /// Continues the search for at most about `budget` options.
+    ///
+    /// The search is resumed where the previous call stopped, whatever its status.
+    pub fn search(&mut self, budget: u64) -> SearchStatus {
+        let start = self.nodes;
+        loop {
+            match self.step {
+                Step::Enter => {
+                    if self.rlink[0] == 0 {
+                        self.step = Step::Leave;
+                        return SearchStatus::Solution;
+                    }
+                    let item = self.choose();
+                    self.cover(item);
+                    self.choices.push(self.dlink[item]);
+                    self.step = Step::Try;
+                }
+                Step::Try => {
+                    let node = *self.choices.last().unwrap();
+                    if node <= self.items() {
+                        // every option of the item has been tried
+                        self.uncover(node);
+                        self.choices.pop();
+                        self.step = Step::Leave;
+                        continue;
+                    }
+                    if self.nodes - start >= budget {
+                        return SearchStatus::Paused;
+                    }
+                    self.nodes += 1;
+
+                    let mut p = node + 1;
+                    while p != node {
+                        let item = self.top[p];
+                        if item <= 0 {
+                            p = self.ulink[p];
+                        } else {
+                            self.commit(p, item as usize);
+                            p += 1;
+                        }
+                    }
+                    self.step = Step::Enter;
+                }
+                Step::Leave => {
+                    let node = match self.choices.last() {
+                        Some(&node) => node,
+                        None => {
+                            self.step = Step::Done;
+                            return SearchStatus::Exhausted;
+                        }
+                    };
+
+                    let mut p = node - 1;
+                    while p != node {
+                        let item = self.top[p];
+                        if item <= 0 {
+                            p = self.dlink[p];
+                        } else {
+                            self.uncommit(p, item as usize);
+                            p -= 1;
+                        }
+                    }
+                    *self.choices.last_mut().unwrap() = self.dlink[node];
+                    self.step = Step::Try;
+                }
+                Step::Done => return SearchStatus::Exhausted,
+            }
+        }
+    }
+
+    /// Number of items, primary and secondary
+    fn items(&self) -> usize {
+        self.llink.len() - 2
+    }
+
+    /// Returns the option of a node, from the spacer before it
+    fn option(&self, node: usize) -> usize {
+        let mut p = node;
+        while self.top[p] > 0 {
+            p -= 1;
+        }
+        (-self.top[p]) as usize
+    }
+
+    /// Appends an option made of these items and colors, then its closing spacer
+    fn add_option(&mut self, row: &[(usize, i32)]) {
+        let spacer = self.top.len() - 1;
+        for &(item, color) in row {
+            let node = self.top.len();
+            let last = self.ulink[item];
+            self.top.push(item as isize);
+            self.ulink.push(last);
+            self.dlink.push(item);
+            self.color.push(color);
+            self.dlink[last] = node;
+            self.ulink[item] = node;
+            self.top[item] += 1;
+        }
+
+        let last = self.top.len() - 1;
+        self.dlink[spacer] = last;
+        self.top.push(-(self.options.len() as isize + 1));
+        self.ulink.push(spacer + 1);
+        self.dlink.push(0);
+        self.color.push(0);
+    }
+
+    /// Returns the primary item with the fewest options left
+    fn choose(&self) -> usize {
+        let mut best = self.rlink[0];
+        let mut item = self.rlink[best];
+        while item != 0 && self.top[best] > 0 {
+            if self.top[item] < self.top[best] {
+                best = item;
+            }
+            item = self.rlink[item];
+        }
+        best
+    }
+
+    fn cover(&mut self, item: usize) {
+        let mut p = self.dlink[item];
+        while p != item {
+            self.hide(p);
+            p = self.dlink[p];
+        }
+        let (l, r) = (self.llink[item], self.rlink[item]);
+        self.rlink[l] = r;
+        self.llink[r] = l;
+    }
+
+    fn uncover(&mut self, item: usize) {
+        let (l, r) = (self.llink[item], self.rlink[item]);
+        self.rlink[l] = item;
+        self.llink[r] = item;
+        let mut p = self.ulink[item];
+        while p != item {
+            self.unhide(p);
+            p = self.ulink[p];
+        }
+    }
+
+    /// Removes the other nodes of the option of `p` from their items
+    fn hide(&mut self, p: usize) {
+        let mut q = p + 1;
+        while q != p {
+            let item = self.top[q];
+            if item <= 0 {
+                q = self.ulink[q];
+            } else {
+                if self.color[q] >= 0 {
+                    let (u, d) = (self.ulink[q], self.dlink[q]);
+                    self.dlink[u] = d;
+                    self.ulink[d] = u;
+                    self.top[item as usize] -= 1;
+                }
+                q += 1;
+            }
+        }
+    }
+
+    fn unhide(&mut self, p: usize) {
+        let mut q = p - 1;
+        while q != p {
+            let item = self.top[q];
+            if item <= 0 {
+                q = self.dlink[q];
+            } else {
+                if self.color[q] >= 0 {
+                    let (u, d) = (self.ulink[q], self.dlink[q]);
+                    self.dlink[u] = q;
+                    self.ulink[d] = q;
+                    self.top[item as usize] += 1;
+                }
+                q -= 1;
+            }
+        }
+    }
+
+    /// Covers an item of a chosen option, or keeps only the options agreeing on its color
+    fn commit(&mut self, p: usize, item: usize) {
+        if self.color[p] == 0 {
+            self.cover(item);
+        } else if self.color[p] > 0 {
+            self.purify(p);
+        }
+    }
+
+    fn uncommit(&mut self, p: usize, item: usize) {
+        if self.color[p] == 0 {
+            self.uncover(item);
+        } else if self.color[p] > 0 {
+            self.unpurify(p);
+        }
+    }
+
+    /// Hides the options giving another color to the secondary item of `p`
+    fn purify(&mut self, p: usize) {
+        let (color, item) = (self.color[p], self.top[p] as usize);
+        let mut q = self.dlink[item];
+        while q != item {
+            if self.color[q] == color {
+                self.color[q] = -1;
+            } else {
+                self.hide(q);
+            }
+            q = self.dlink[q];
+        }
+    }
+
+    fn unpurify(&mut self, p: usize) {
+        let (color, item) = (self.color[p], self.top[p] as usize);
+        let mut q = self.ulink[item];
+        while q != item {
+            if self.color[q] < 0 {
+                self.color[q] = color;
+            } else {
+                self.unhide(q);
+            }
+            q = self.ulink[q];
+        }
+    }
+}
+
+impl Iterator for Dlx {
+    type Item = BoardGame;
+
+    fn next(&mut self) -> Option<BoardGame> {
+        self.next_solution()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::create_board;
+    use generate::{generate, GeneratorConfig};
+    use puzzle::{Hint, PuzzleFile};
+    use solution::{verify, Solution};
+
+    #[test]
+    fn test_options() {
+        let dlx = Dlx::new(self::create_board());
+        // corners and borders in their single orientation, full pieces in four
+        assert_eq!(dlx.options(), 4 * 4 + 8 * 8 + 4 * 4 * 4);
+
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        assert_eq!(Dlx::new(board).options(), 4 * 4 + 8 * 8 + 1 + 3 * 3 * 4);
+    }
+
+    #[test]
+    fn test_solutions() {
+        let board = self::create_board();
+        let puzzle = PuzzleFile::parse(include_str!("../pieces_4x4.txt")).unwrap();
+
+        let mut dlx = Dlx::new(board.clone());
+        let solved = dlx.next_solution().unwrap();
+        assert_eq!(verify(&puzzle, &Solution::from_board(&solved)), Ok(()));
+        assert_eq!(dlx.count_solutions() + 1, board.count_solutions(false));
+        assert_eq!(dlx.search(u64::MAX), SearchStatus::Exhausted);
+    }
+
+    #[test]
+    fn test_hints() {
+        let mut board = self::create_board();
+        board
+            .add_hint(Hint {
+                piece: 12,
+                pos: (1, 1),
+                compass: Compass::South,
+            })
+            .unwrap();
+
+        let mut dlx = Dlx::new(board.clone());
+        for solved in dlx.by_ref().take(1) {
+            assert_eq!(solved.cells[1][1], board.cells[1][1]);
+        }
+        let all = board.count_solutions(false);
+        assert!(all > 0);
+        assert_eq!(Dlx::new(board).count_solutions(), all);
+    }
+
+    #[test]
+    fn test_impossible() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+
+        let mut dlx = Dlx::new(board);
+        assert_eq!(dlx.next_solution().map(|_| ()), None);
+        assert_eq!(dlx.count_solutions(), 0);
+    }
+
+    #[test]
+    fn test_generated() {
+        let (puzzle, solution) = generate(&GeneratorConfig {
+            width: 5,
+            height: 4,
+            border_colors: 3,
+            inner_colors: 4,
+            seed: 7,
+        });
+        let board = BoardGame::from_puzzle(puzzle.clone());
+        let all = board.count_solutions(false);
+
+        let mut dlx = Dlx::new(board);
+        let mut found = Vec::new();
+        loop {
+            match dlx.search(7) {
+                SearchStatus::Solution => found.push(Solution::from_board(&dlx.board())),
+                SearchStatus::Paused => (),
+                _ => break,
+            }
+        }
+        assert_eq!(found.len(), all);
+        assert!(found.contains(&solution));
+        for solved in &found {
+            assert_eq!(verify(&puzzle, solved), Ok(()));
+        }
+    }
+}
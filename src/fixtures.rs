@@ -0,0 +1,8 @@
+use board::BoardGame;
+
+/// Board of `pieces_4x4.txt`, the 4x4 puzzle the tests of the crate share
+pub(crate) fn create_board() -> BoardGame {
+    let file_content = include_str!("../pieces_4x4.txt");
+
+    BoardGame::new(file_content.lines().map(|line| line.to_string()).collect())
+}
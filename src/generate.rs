@@ -0,0 +1,252 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use board::Compass;
+use cell::Face;
+use piece::Piece;
+use puzzle::{is_canonical, Puzzle, PuzzleFile, MAX_PIECES};
+use rng::Rng;
+use solution::Solution;
+
+/// Settings of `generate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    pub width: u16,
+    pub height: u16,
+    /// Colors of the edges between two pieces of the frame, numbered from `1`
+    pub border_colors: u16,
+    /// Colors of the other edges, numbered after the border colors
+    pub inner_colors: u16,
+    pub seed: u64,
+}
+
+/// Creates a puzzle with a known solution.
+///
+/// Every internal edge of a `width` by `height` grid gets a random color, the edges between two
+/// cells of the frame a border color and the others an inner color, as in Eternity II. The grid
+/// is then cut in pieces, turned and shuffled. The same config always gives the same puzzle.
+///
+/// # Panics
+///
+/// if a side is shorter than `2`, if there are more pieces than ids, or if there are no border
+/// or no inner colors
+pub fn generate(config: &GeneratorConfig) -> (Puzzle, Solution) {
+    let (width, height) = (config.width, config.height);
+    assert!(
+        width >= 2 && height >= 2,
+        "the board needs at least 2x2 cells"
+    );
+    assert!(
+        (width as usize) * (height as usize) <= MAX_PIECES,
+        "too many pieces for 16 bit ids"
+    );
+    assert!(
+        config.border_colors > 0 && config.inner_colors > 0,
+        "border and inner colors are needed"
+    );
+    let colors = config
+        .border_colors
+        .checked_add(config.inner_colors)
+        .expect("too many colors");
+
+    let mut rng = Rng::new(config.seed);
+    let (w, h) = (width as usize, height as usize);
+    let on_frame = |x: usize, y: usize| x == 0 || y == 0 || x == w - 1 || y == h - 1;
+    let mut color = |frame_edge: bool| {
+        if frame_edge {
+            1 + rng.below(config.border_colors as usize) as u16
+        } else {
+            config.border_colors + 1 + rng.below(config.inner_colors as usize) as u16
+        }
+    };
+
+    // east side of each cell, then south side of each cell, 0 facing the frame
+    let mut east = vec![vec![0; w]; h];
+    let mut south = vec![vec![0; w]; h];
+    for y in 0..h {
+        for x in 0..w {
+            if x + 1 < w {
+                east[y][x] = color((y == 0 || y == h - 1) && on_frame(x + 1, y));
+            }
+            if y + 1 < h {
+                south[y][x] = color((x == 0 || x == w - 1) && on_frame(x, y + 1));
+            }
+        }
+    }
+
+    let mut pieces = Vec::with_capacity(w * h);
+    for y in 0..h {
+        for x in 0..w {
+            let north = if y > 0 { south[y - 1][x] } else { 0 };
+            let west = if x > 0 { east[y][x - 1] } else { 0 };
+            let mut sides = [north, east[y][x], south[y][x], west];
+
+            if on_frame(x, y) {
+                while !is_canonical(&sides) {
+                    sides.rotate_left(1);
+                }
+            } else {
+                let turn = rng.below(4);
+                sides.rotate_left(turn);
+            }
+            let faces = |sides: [u16; 4]| {
+                let face = |side: u16| match side {
+                    0 => Face::Border,
+                    color => Face::Color(color),
+                };
+                (
+                    face(sides[0]),
+                    face(sides[1]),
+                    face(sides[2]),
+                    face(sides[3]),
+                )
+            };
+            let expected = faces([north, east[y][x], south[y][x], west]);
+            pieces.push(((x as u16, y as u16), sides, expected));
+        }
+    }
+    rng.shuffle(&mut pieces);
+
+    let mut cells = vec![None; w * h];
+    let pieces = pieces
+        .into_iter()
+        .enumerate()
+        .map(|(id, ((x, y), sides, expected))| {
+            let piece = Piece::new(id as u16, sides.to_vec());
+            let compass = *Compass::all()
+                .iter()
+                .find(|compass| piece.get_faces(compass) == expected)
+                .expect("every piece fits its own cell");
+            cells[(y as usize) * w + (x as usize)] = Some((id as u16, compass));
+            piece
+        })
+        .collect();
+
+    let puzzle = Puzzle {
+        width,
+        height,
+        colors,
+        pieces,
+        hints: Vec::new(),
+    };
+    let solution = Solution {
+        width,
+        height,
+        cells,
+    };
+    (puzzle, solution)
+}
+
+/// Generates a puzzle and writes it to `puzzle_path`, and its solution to `solution_path`
+pub fn write(config: &GeneratorConfig, puzzle_path: &Path, solution_path: &Path) -> io::Result<()> {
+    let (puzzle, solution) = generate(config);
+    fs::write(puzzle_path, PuzzleFile::write(&puzzle))?;
+    fs::write(solution_path, solution.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::BoardGame;
+    use piece::{PieceKind, Sides};
+    use solution::verify;
+    use std::env;
+
+    fn config(width: u16, height: u16, seed: u64) -> GeneratorConfig {
+        GeneratorConfig {
+            width,
+            height,
+            border_colors: 3,
+            inner_colors: 4,
+            seed,
+        }
+    }
+
+    #[test]
+    fn test_generate_verifies() {
+        for &(width, height) in &[(2, 2), (3, 2), (4, 4), (7, 5), (16, 16)] {
+            let (puzzle, solution) = generate(&self::config(width, height, 42));
+
+            assert_eq!((puzzle.width, puzzle.height), (width, height));
+            assert_eq!(puzzle.pieces.len(), (width as usize) * (height as usize));
+            assert_eq!(verify(&puzzle, &solution), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_generate_colors() {
+        let (puzzle, _) = generate(&self::config(6, 5, 1));
+
+        assert_eq!(puzzle.colors, 7);
+        for piece in &puzzle.pieces {
+            let sides = piece.get_props().kind;
+            let (border, inner): (Vec<u16>, Vec<u16>) = match sides {
+                Sides::Corner(a, b) => (vec![a, b], vec![]),
+                Sides::Border(a, b, c) => (vec![a, c], vec![b]),
+                Sides::Full(a, b, c, d) => (vec![], vec![a, b, c, d]),
+            };
+            assert!(border.iter().all(|color| (1..=3).contains(color)));
+            assert!(inner.iter().all(|color| (4..=7).contains(color)));
+        }
+        let count = |kind| {
+            puzzle
+                .pieces
+                .iter()
+                .filter(|piece| piece.get_kind() == kind)
+                .count()
+        };
+        assert_eq!(count(PieceKind::Corner), 4);
+        assert_eq!(count(PieceKind::Border), 14);
+    }
+
+    #[test]
+    fn test_generate_reproducible() {
+        assert_eq!(
+            generate(&self::config(5, 5, 9)),
+            generate(&self::config(5, 5, 9))
+        );
+        assert_ne!(
+            generate(&self::config(5, 5, 9)).0,
+            generate(&self::config(5, 5, 10)).0
+        );
+    }
+
+    #[test]
+    fn test_generate_solvable() {
+        let (puzzle, solution) = generate(&self::config(4, 3, 3));
+        let content = PuzzleFile::write(&puzzle);
+        assert_eq!(PuzzleFile::parse(&content), Ok(puzzle.clone()));
+        assert_eq!(Solution::parse(&solution.to_string()), Ok(solution.clone()));
+
+        let board = BoardGame::from_puzzle(puzzle.clone());
+        let found = board.solve().unwrap();
+        assert_eq!(verify(&puzzle, &Solution::from_board(&found)), Ok(()));
+    }
+
+    #[test]
+    fn test_generate_write() {
+        let dir = env::temp_dir();
+        let puzzle_path = dir.join(format!("eternity-generate-{}.txt", std::process::id()));
+        let solution_path = puzzle_path.with_extension("solution");
+        let config = self::config(3, 3, 5);
+
+        write(&config, &puzzle_path, &solution_path).unwrap();
+        let puzzle = PuzzleFile::parse(&fs::read_to_string(&puzzle_path).unwrap()).unwrap();
+        let solution = Solution::parse(&fs::read_to_string(&solution_path).unwrap()).unwrap();
+        fs::remove_file(&puzzle_path).unwrap();
+        fs::remove_file(&solution_path).unwrap();
+
+        assert_eq!((puzzle.clone(), solution.clone()), generate(&config));
+        assert_eq!(verify(&puzzle, &solution), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "border and inner colors are needed")]
+    fn test_generate_no_colors() {
+        generate(&GeneratorConfig {
+            inner_colors: 0,
+            ..self::config(4, 4, 0)
+        });
+    }
+}
@@ -0,0 +1,378 @@
+use board::{BoardGame, Compass, MoveError};
+
+/// Change made to a board, with what is needed to reverse it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Move {
+    /// A piece put by `BoardGame::put_piece`, with the compass it was given
+    Put {
+        piece: u16,
+        pos: (u16, u16),
+        compass: Option<Compass>,
+    },
+    /// A piece placed by `BoardGame::place_piece`, it matched its neighbors
+    Place {
+        piece: u16,
+        pos: (u16, u16),
+        compass: Option<Compass>,
+    },
+    /// A full piece turned `from` one compass `to` another
+    Rotate {
+        pos: (u16, u16),
+        from: Compass,
+        to: Compass,
+    },
+    /// A piece taken off the board, the compass is the one of a full piece
+    Remove {
+        piece: u16,
+        pos: (u16, u16),
+        compass: Option<Compass>,
+    },
+}
+
+/// Moves done on a board, and the moves undone since the last new move
+#[derive(Debug)]
+pub struct History {
+    done: Vec<Move>,
+    undone: Vec<Move>,
+    recording: bool,
+    /// Set on the board of a search, which never records whatever `recording` says
+    suspended: bool,
+}
+
+impl History {
+    /// Creates an empty history, recording moves
+    pub fn new() -> Self {
+        History {
+            done: Vec::new(),
+            undone: Vec::new(),
+            recording: true,
+            suspended: false,
+        }
+    }
+
+    /// Logs a new move, the undone moves cannot be redone anymore
+    pub(crate) fn record(&mut self, done: Move) {
+        if self.recording && !self.suspended {
+            self.done.push(done);
+            self.undone.clear();
+        }
+    }
+}
+
+/// A copy is not suspended: the boards handed out by a search record as the board it was given
+impl Clone for History {
+    fn clone(&self) -> Self {
+        History {
+            done: self.done.clone(),
+            undone: self.undone.clone(),
+            recording: self.recording,
+            suspended: false,
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+impl BoardGame {
+    /// Returns the moves done on the board since it was created, oldest first, without the undone
+    /// moves.
+    ///
+    /// Hints are not moves, they are on the board from the start.
+    pub fn moves(&self) -> &[Move] {
+        &self.history.done
+    }
+
+    /// Turns the recording of moves on or off, it is on for new boards.
+    ///
+    /// Solvers and annealers never record on their own boards, their moves are not worth keeping,
+    /// but the boards they return keep the setting of the board they were given.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.history.recording = recording;
+    }
+
+    /// Forgets every move, the board stays as it is
+    pub fn clear_history(&mut self) {
+        self.history.done.clear();
+        self.history.undone.clear();
+    }
+
+    /// Forgets every move and stops recording until the board is copied, for the boards of
+    /// searches
+    pub(crate) fn discard_history(&mut self) {
+        self.clear_history();
+        self.history.suspended = true;
+    }
+
+    /// Reverses the last move.
+    ///
+    /// # Returns
+    ///
+    /// The undone move, `None` if there is nothing to undo, or `Err` if the board was changed
+    /// without recording in a way that prevents it. The history is left untouched on `Err`.
+    pub fn undo(&mut self) -> Result<Option<Move>, MoveError> {
+        let last = match self.history.done.last() {
+            Some(&last) => last,
+            None => return Ok(None),
+        };
+
+        match last {
+            Move::Put { pos, .. } | Move::Place { pos, .. } => {
+                self.take_piece(pos)?;
+            }
+            Move::Rotate { pos, from, .. } => {
+                self.turn_piece(pos, from)?;
+            }
+            Move::Remove {
+                piece,
+                pos,
+                compass,
+            } => self.set_piece(piece, pos, compass)?,
+        }
+        self.history.done.pop();
+        self.history.undone.push(last);
+
+        Ok(Some(last))
+    }
+
+    /// Does again the last undone move, see `BoardGame::undo`
+    pub fn redo(&mut self) -> Result<Option<Move>, MoveError> {
+        let next = match self.history.undone.last() {
+            Some(&next) => next,
+            None => return Ok(None),
+        };
+
+        match next {
+            Move::Put {
+                piece,
+                pos,
+                compass,
+            }
+            | Move::Place {
+                piece,
+                pos,
+                compass,
+            } => self.set_piece(piece, pos, compass)?,
+            Move::Rotate { pos, to, .. } => {
+                self.turn_piece(pos, to)?;
+            }
+            Move::Remove { pos, .. } => {
+                self.take_piece(pos)?;
+            }
+        }
+        self.history.undone.pop();
+        self.history.done.push(next);
+
+        Ok(Some(next))
+    }
+
+    /// Plays the moves in order, usually the `BoardGame::moves` of another board of the same
+    /// puzzle.
+    ///
+    /// Each move goes through the checks of its method, places are checked against the neighbors
+    /// again, and is recorded.
+    ///
+    /// # Returns
+    ///
+    /// `Err` for the first move that cannot be played, the moves before it stay on the board.
+    pub fn replay(&mut self, moves: &[Move]) -> Result<(), MoveError> {
+        for &played in moves {
+            match played {
+                Move::Put {
+                    piece,
+                    pos,
+                    compass,
+                } => self.put_piece(piece, pos, compass)?,
+                Move::Place {
+                    piece,
+                    pos,
+                    compass,
+                } => self.place_piece(piece, pos, compass)?,
+                Move::Rotate { pos, to, .. } => self.try_rotate_piece(pos, to)?,
+                Move::Remove { piece, pos, .. } => {
+                    self.check_move(pos)?;
+                    let (x, y) = pos;
+                    if let Some(props) = self.cells[y as usize][x as usize].get_props() {
+                        if props.id != piece {
+                            return Err(MoveError::PieceMismatch {
+                                pos,
+                                expected: piece,
+                                found: props.id,
+                            });
+                        }
+                    }
+                    self.try_remove_piece(pos)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::create_board;
+    use puzzle::PuzzleFile;
+
+    #[test]
+    fn test_moves() {
+        let mut board = self::create_board();
+        board.put_piece(3, (0, 0), None).unwrap();
+        board.place_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.rotate_piece((1, 1), Compass::South);
+        board.remove_piece((0, 0));
+        assert!(board.put_piece(12, (2, 2), Some(Compass::North)).is_err());
+
+        assert_eq!(
+            board.moves(),
+            &[
+                Move::Put {
+                    piece: 3,
+                    pos: (0, 0),
+                    compass: None
+                },
+                Move::Place {
+                    piece: 12,
+                    pos: (1, 1),
+                    compass: Some(Compass::North)
+                },
+                Move::Rotate {
+                    pos: (1, 1),
+                    from: Compass::North,
+                    to: Compass::South
+                },
+                Move::Remove {
+                    piece: 3,
+                    pos: (0, 0),
+                    compass: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut board = self::create_board();
+        assert_eq!(board.undo(), Ok(None));
+        board.put_piece(3, (0, 0), None).unwrap();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.rotate_piece((1, 1), Compass::East);
+        board.remove_piece((0, 0));
+
+        assert!(board.undo().unwrap().is_some());
+        assert!(board.placed[3]);
+        assert_eq!(board.cells[0][0].get_props().map(|props| props.id), Some(3));
+
+        board.undo().unwrap();
+        assert_eq!(board.cells[1][1].get_compass(), Some(Compass::North));
+        board.undo().unwrap();
+        assert!(board.cells[1][1].is_empty());
+        assert!(!board.placed[12]);
+        assert_eq!(board.moves().len(), 1);
+
+        assert!(board.redo().unwrap().is_some());
+        board.redo().unwrap();
+        board.redo().unwrap();
+        assert_eq!(board.redo(), Ok(None));
+        assert!(board.cells[0][0].is_empty());
+        assert!(!board.placed[3]);
+        assert_eq!(board.cells[1][1].get_compass(), Some(Compass::East));
+        assert_eq!(board.moves().len(), 4);
+
+        board.undo().unwrap();
+        board.remove_piece((1, 1));
+        assert_eq!(board.redo(), Ok(None));
+    }
+
+    #[test]
+    fn test_undo_unrecorded() {
+        let mut board = self::create_board();
+        board.put_piece(3, (0, 0), None).unwrap();
+        board.set_recording(false);
+        board.remove_piece((0, 0));
+        board.set_recording(true);
+
+        assert_eq!(board.moves().len(), 1);
+        assert_eq!(board.undo(), Err(MoveError::EmptyCell { pos: (0, 0) }));
+        assert_eq!(board.moves().len(), 1);
+
+        board.clear_history();
+        assert_eq!(board.undo(), Ok(None));
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut board = self::create_board();
+        board.put_piece(3, (0, 0), None).unwrap();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.rotate_piece((1, 1), Compass::West);
+        board.put_piece(0, (3, 0), None).unwrap();
+        board.remove_piece((3, 0));
+
+        let mut fresh = self::create_board();
+        fresh.replay(board.moves()).unwrap();
+        assert_eq!(fresh.cells, board.cells);
+        assert_eq!(fresh.placed, board.placed);
+        assert_eq!(fresh.moves(), board.moves());
+
+        let mismatch = [Move::Place {
+            piece: 13,
+            pos: (2, 1),
+            compass: Some(Compass::South),
+        }];
+        let mut fresh =
+            BoardGame::from_puzzle(PuzzleFile::parse(include_str!("../pieces_4x4.txt")).unwrap());
+        fresh.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        assert!(fresh.replay(&mismatch).is_err());
+        assert_eq!(fresh.moves().len(), 1);
+
+        let other = [Move::Remove {
+            piece: 13,
+            pos: (1, 1),
+            compass: Some(Compass::North),
+        }];
+        assert_eq!(
+            fresh.replay(&other),
+            Err(MoveError::PieceMismatch {
+                pos: (1, 1),
+                expected: 13,
+                found: 12
+            })
+        );
+        assert!(fresh.placed[12]);
+        assert_eq!(fresh.moves().len(), 1);
+    }
+
+    #[test]
+    fn test_solved_board_records() {
+        let mut board = self::create_board().solve().unwrap();
+        let solved = board.clone();
+        assert!(board.moves().is_empty());
+
+        board.remove_piece((0, 0));
+        assert_eq!(board.moves().len(), 1);
+        assert!(board.undo().unwrap().is_some());
+        assert_eq!(board.cells, solved.cells);
+
+        let mut quiet = self::create_board();
+        quiet.set_recording(false);
+        let mut solved = quiet.solve().unwrap();
+        solved.remove_piece((0, 0));
+        assert_eq!(solved.undo(), Ok(None));
+    }
+
+    #[test]
+    fn test_hints_not_recorded() {
+        let content = include_str!("../pieces_4x4.txt").to_string() + "\nhint 12 1 1 S\n";
+        let mut board = BoardGame::new(content.lines().map(|line| line.to_string()).collect());
+
+        assert!(board.moves().is_empty());
+        assert_eq!(board.undo(), Ok(None));
+        assert!(board.placed[12]);
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use board::Compass;
+use cell::Border::{East, North, South, West};
+use cell::{CellKind, Face};
+use piece::Piece;
+
+/// Lookup table of the pieces fitting a cell, built once for all the pieces of a board.
+///
+/// Candidates are keyed by the north and west faces the piece shows once oriented, and the kind
+/// of the cell. `Face::None` stands for a side that can take any color, so a cell whose
+/// neighbors are still empty is looked up with the face returned by `BoardGame::get_frontier`.
+#[derive(Debug, Clone, Default)]
+pub struct CandidateIndex {
+    candidates: HashMap<(Face, Face, CellKind), Vec<(u16, Compass)>>,
+}
+
+impl CandidateIndex {
+    pub fn new(pieces: &[Piece]) -> Self {
+        let kinds = [
+            CellKind::Corner(North, West),
+            CellKind::Corner(North, East),
+            CellKind::Corner(South, East),
+            CellKind::Corner(South, West),
+            CellKind::Border(North),
+            CellKind::Border(East),
+            CellKind::Border(South),
+            CellKind::Border(West),
+            CellKind::Full,
+        ];
+
+        let mut candidates: HashMap<_, Vec<_>> = HashMap::new();
+        for kind in &kinds {
+            let compasses = match kind.get_compass() {
+                Some(compass) => vec![compass],
+                None => Compass::all().to_vec(),
+            };
+
+            for piece in pieces {
+                let id = match (kind, piece) {
+                    (CellKind::Corner(..), Piece::CornerPiece(props))
+                    | (CellKind::Border(_), Piece::BorderPiece(props))
+                    | (CellKind::Full, Piece::FullPiece(props)) => props.id,
+                    _ => continue,
+                };
+
+                for compass in &compasses {
+                    let (north, _, _, west) = piece.get_faces(compass);
+                    for key in &[
+                        (north, west),
+                        (Face::None, west),
+                        (north, Face::None),
+                        (Face::None, Face::None),
+                    ] {
+                        candidates
+                            .entry((key.0, key.1, *kind))
+                            .or_default()
+                            .push((id, *compass));
+                    }
+                }
+            }
+        }
+
+        Self { candidates }
+    }
+
+    /// Returns the oriented pieces showing these faces on a cell of this kind.
+    ///
+    /// Pieces already placed on the board are part of the result.
+    pub fn get(&self, north: Face, west: Face, kind: CellKind) -> &[(u16, Compass)] {
+        self.candidates
+            .get(&(north, west, kind))
+            .map(|candidates| candidates.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_pieces() -> Vec<Piece> {
+        vec![
+            Piece::new(0, vec![0, 0, 1, 2]),
+            Piece::new(1, vec![0, 1, 3, 2]),
+            Piece::new(2, vec![0, 2, 3, 1]),
+            Piece::new(3, vec![3, 4, 3, 5]),
+        ]
+    }
+
+    #[test]
+    fn test_get_corner() {
+        let index = CandidateIndex::new(&self::create_pieces());
+
+        assert_eq!(
+            index.get(Face::Border, Face::Border, CellKind::Corner(North, West)),
+            &[(0, Compass::East)]
+        );
+        assert_eq!(
+            index.get(Face::Border, Face::Color(2), CellKind::Corner(North, East)),
+            &[(0, Compass::South)]
+        );
+        assert_eq!(
+            index.get(Face::Border, Face::Color(1), CellKind::Corner(North, East)),
+            &[]
+        );
+        assert_eq!(
+            index.get(Face::Color(1), Face::None, CellKind::Corner(South, West)),
+            &[(0, Compass::North)]
+        );
+    }
+
+    #[test]
+    fn test_get_border() {
+        let index = CandidateIndex::new(&self::create_pieces());
+
+        assert_eq!(
+            index.get(Face::Border, Face::Color(2), CellKind::Border(North)),
+            &[(1, Compass::East)]
+        );
+        assert_eq!(
+            index.get(Face::Border, Face::None, CellKind::Border(North)),
+            &[(1, Compass::East), (2, Compass::East)]
+        );
+        assert_eq!(
+            index.get(Face::Color(2), Face::Border, CellKind::Border(West)),
+            &[(2, Compass::North)]
+        );
+    }
+
+    #[test]
+    fn test_get_full() {
+        let index = CandidateIndex::new(&self::create_pieces());
+
+        assert_eq!(
+            index.get(Face::Color(3), Face::Color(5), CellKind::Full),
+            &[(3, Compass::North)]
+        );
+        assert_eq!(
+            index.get(Face::Color(3), Face::None, CellKind::Full),
+            &[(3, Compass::North), (3, Compass::South)]
+        );
+        assert_eq!(index.get(Face::None, Face::None, CellKind::Full).len(), 4);
+        assert_eq!(
+            index.get(Face::Color(1), Face::Color(2), CellKind::Full),
+            &[]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Solver and tools for edge matching puzzles like Eternity II.
+//!
+//! A puzzle is read with `PuzzleFile::parse`, its pieces are laid out on a `BoardGame`, and the
+//! board is solved with `BoardGame::solve`, a `Solver` or a `ParallelSolver`:
+//!
+//! ```
+//! use rust_eternity::{BoardGame, PuzzleFile};
+//!
+//! let puzzle = PuzzleFile::parse("2 2\n1\n1\n1 1 1 1\n0 0 1 1\n0 0 1 1\n0 0 1 1\n0 0 1 1").unwrap();
+//! let board = BoardGame::from_puzzle(puzzle);
+//!
+//! let solved = board.solve().unwrap();
+//! assert_eq!(solved.score(), solved.max_score());
+//! println!("{}", solved);
+//! ```
+
+pub mod anneal;
+pub mod board;
+pub mod cell;
+pub mod checkpoint;
+pub mod dlx;
+#[cfg(test)]
+mod fixtures;
+pub mod generate;
+pub mod history;
+pub mod index;
+pub mod mrv;
+pub mod parallel;
+pub mod piece;
+pub mod propagation;
+pub mod puzzle;
+pub mod render;
+mod rng;
+pub mod scan;
+pub mod score;
+pub mod solution;
+pub mod solver;
+pub mod svg;
+
+pub use anneal::{AnnealConfig, Annealer};
+pub use board::{BoardGame, Compass, MoveError, PlacementError};
+pub use cell::{Border, Cell, CellKind, Face};
+pub use checkpoint::{Checkpoint, CheckpointError};
+pub use dlx::Dlx;
+pub use generate::{generate, GeneratorConfig};
+pub use history::Move;
+pub use mrv::{CandidateCounts, Degrees};
+pub use parallel::ParallelSolver;
+pub use piece::{Piece, PieceKind, Sides};
+pub use propagation::Domains;
+pub use puzzle::{BenchmarkFile, Hint, ParseError, ParseErrorKind, Puzzle, PuzzleFile};
+pub use render::Render;
+pub use scan::ScanOrder;
+pub use solution::{verify, Problem, Solution};
+pub use solver::{SearchState, SearchStatus, Solver};
+pub use svg::SvgOptions;
@@ -0,0 +1,269 @@
+use board::BoardGame;
+use cell::Face;
+use scan::ScanOrder;
+
+/// Number of unplaced oriented pieces fitting each empty cell of a board.
+///
+/// A piece fits a cell when its four faces match the `BoardGame::get_frontier` of the cell, an
+/// empty neighbor accepting any color. The counts follow the board through `CandidateCounts::place`
+/// and `CandidateCounts::remove`: only the cell and its neighbors are counted again, and only the
+/// cells the moved piece fits lose or regain it, found through the cells kept for each piece.
+#[derive(Debug, Clone)]
+pub struct CandidateCounts {
+    width: u16,
+    counts: Vec<usize>,
+    /// Oriented pieces fitting each empty cell, placed or not, with the index of the matching
+    /// entry in `cells`
+    fitting: Vec<Vec<(u16, u32)>>,
+    /// Cells each piece fits, once per orientation, with the index of the matching entry in
+    /// `fitting`
+    cells: Vec<Vec<(u32, u32)>>,
+}
+
+impl CandidateCounts {
+    /// Counts the candidates of every empty cell of the board
+    pub fn new(board: &BoardGame) -> Self {
+        let size = (board.width as usize) * (board.height as usize);
+        let mut counts = CandidateCounts {
+            width: board.width,
+            counts: vec![0; size],
+            fitting: vec![Vec::new(); size],
+            cells: vec![Vec::new(); board.pieces.len()],
+        };
+        for (pos, cell) in board.iter_cells(&ScanOrder::RowMajor) {
+            if cell.is_empty() {
+                counts.recount(board, pos);
+            }
+        }
+        counts
+    }
+
+    /// Returns the number of oriented pieces fitting the cell, `0` for a filled cell
+    pub fn get(&self, pos: (u16, u16)) -> usize {
+        self.counts[self.cell(pos)]
+    }
+
+    /// Updates the counts once the piece is placed at the position on the board
+    pub fn place(&mut self, board: &BoardGame, pos: (u16, u16), piece: u16) {
+        for &(cell, _) in &self.cells[piece as usize] {
+            self.counts[cell as usize] -= 1;
+        }
+        let cell = self.cell(pos);
+        self.forget(cell);
+        self.counts[cell] = 0;
+        self.recount_neighbors(board, pos);
+    }
+
+    /// Updates the counts once the piece is removed from the position on the board
+    pub fn remove(&mut self, board: &BoardGame, pos: (u16, u16), piece: u16) {
+        for &(cell, _) in &self.cells[piece as usize] {
+            self.counts[cell as usize] += 1;
+        }
+        self.recount(board, pos);
+        self.recount_neighbors(board, pos);
+    }
+
+    fn recount_neighbors(&mut self, board: &BoardGame, pos: (u16, u16)) {
+        for &other in neighbors(board, pos).iter().flatten() {
+            if board.cells[other.1 as usize][other.0 as usize].is_empty() {
+                self.recount(board, other);
+            }
+        }
+    }
+
+    fn recount(&mut self, board: &BoardGame, pos: (u16, u16)) {
+        let cell = self.cell(pos);
+        self.forget(cell);
+
+        let (_, east, south, _) = board.get_frontier(pos);
+        let mut count = 0;
+        for &(id, compass) in board.candidates(pos) {
+            let (_, e, s, _) = board.pieces[id as usize].get_faces(&compass);
+            if (east == Face::None || east == e) && (south == Face::None || south == s) {
+                let cells = &mut self.cells[id as usize];
+                self.fitting[cell].push((id, cells.len() as u32));
+                cells.push((cell as u32, (self.fitting[cell].len() - 1) as u32));
+                if !board.placed[id as usize] {
+                    count += 1;
+                }
+            }
+        }
+        self.counts[cell] = count;
+    }
+
+    /// Removes the pieces fitting the cell from the cells of each piece
+    fn forget(&mut self, cell: usize) {
+        while let Some((id, index)) = self.fitting[cell].pop() {
+            let cells = &mut self.cells[id as usize];
+            let last = cells.pop().unwrap();
+            if (index as usize) < cells.len() {
+                cells[index as usize] = last;
+                self.fitting[last.0 as usize][last.1 as usize].1 = index;
+            }
+        }
+    }
+
+    fn cell(&self, pos: (u16, u16)) -> usize {
+        (pos.1 as usize) * (self.width as usize) + pos.0 as usize
+    }
+}
+
+/// Number of empty neighbors of each cell of a board, see `degree`.
+///
+/// Like `CandidateCounts`, the degrees follow the board through `Degrees::place` and
+/// `Degrees::remove`, which only change the neighbors of the cell.
+#[derive(Debug, Clone)]
+pub struct Degrees {
+    width: u16,
+    degrees: Vec<u8>,
+}
+
+impl Degrees {
+    /// Counts the empty neighbors of every cell of the board
+    pub fn new(board: &BoardGame) -> Self {
+        let degrees = board
+            .iter_cells(&ScanOrder::RowMajor)
+            .map(|(pos, _)| degree(board, pos) as u8)
+            .collect();
+        Degrees {
+            width: board.width,
+            degrees,
+        }
+    }
+
+    /// Returns the number of empty neighbors of the cell
+    pub fn get(&self, pos: (u16, u16)) -> usize {
+        self.degrees[(pos.1 as usize) * (self.width as usize) + pos.0 as usize] as usize
+    }
+
+    /// Updates the degrees once a piece is placed at the position on the board
+    pub fn place(&mut self, board: &BoardGame, pos: (u16, u16)) {
+        for &(x, y) in neighbors(board, pos).iter().flatten() {
+            self.degrees[(y as usize) * (self.width as usize) + x as usize] -= 1;
+        }
+    }
+
+    /// Updates the degrees once a piece is removed from the position on the board
+    pub fn remove(&mut self, board: &BoardGame, pos: (u16, u16)) {
+        for &(x, y) in neighbors(board, pos).iter().flatten() {
+            self.degrees[(y as usize) * (self.width as usize) + x as usize] += 1;
+        }
+    }
+}
+
+/// Returns the number of empty neighbors of the position
+pub fn degree(board: &BoardGame, pos: (u16, u16)) -> usize {
+    neighbors(board, pos)
+        .iter()
+        .flatten()
+        .filter(|&&(x, y)| board.cells[y as usize][x as usize].is_empty())
+        .count()
+}
+
+/// Returns the north, east, south and west neighbors of the position inside the board
+fn neighbors(board: &BoardGame, pos: (u16, u16)) -> [Option<(u16, u16)>; 4] {
+    let (x, y) = pos;
+    [
+        if y > 0 { Some((x, y - 1)) } else { None },
+        if x + 1 < board.width {
+            Some((x + 1, y))
+        } else {
+            None
+        },
+        if y + 1 < board.height {
+            Some((x, y + 1))
+        } else {
+            None
+        },
+        if x > 0 { Some((x - 1, y)) } else { None },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Compass;
+    use fixtures::create_board;
+
+    fn assert_counts(counts: &CandidateCounts, board: &BoardGame) {
+        let fresh = CandidateCounts::new(board);
+        for (pos, _) in board.iter_cells(&ScanOrder::RowMajor) {
+            assert_eq!(counts.get(pos), fresh.get(pos), "{:?}", pos);
+        }
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut board = self::create_board();
+        let counts = CandidateCounts::new(&board);
+
+        assert_eq!(counts.get((0, 0)), 4);
+        assert_eq!(counts.get((1, 0)), 8);
+        assert_eq!(counts.get((1, 1)), 16);
+
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        let counts = CandidateCounts::new(&board);
+        assert_eq!(counts.get((1, 1)), 0);
+        // border pieces showing 3 to the south
+        assert_eq!(counts.get((1, 0)), 2);
+        assert_eq!(counts.get((2, 2)), 12);
+    }
+
+    #[test]
+    fn test_place_remove() {
+        let mut board = self::create_board();
+        let mut counts = CandidateCounts::new(&board);
+        let solution = board.solve().unwrap();
+
+        let mut placed = Vec::new();
+        for (pos, cell) in solution.iter_cells(&ScanOrder::Spiral) {
+            let id = cell.get_props().unwrap().id;
+            board.put_piece(id, pos, cell.get_compass()).unwrap();
+            counts.place(&board, pos, id);
+            assert_counts(&counts, &board);
+            placed.push((pos, id));
+        }
+        while let Some((pos, id)) = placed.pop() {
+            board.remove_piece(pos);
+            counts.remove(&board, pos, id);
+            assert_counts(&counts, &board);
+        }
+    }
+
+    #[test]
+    fn test_degrees() {
+        let mut board = self::create_board();
+        let mut degrees = Degrees::new(&board);
+        let solution = board.solve().unwrap();
+
+        let mut placed = Vec::new();
+        for (pos, cell) in solution.iter_cells(&ScanOrder::Diagonal) {
+            board
+                .put_piece(cell.get_props().unwrap().id, pos, cell.get_compass())
+                .unwrap();
+            degrees.place(&board, pos);
+            placed.push(pos);
+            for (other, _) in board.iter_cells(&ScanOrder::RowMajor) {
+                assert_eq!(degrees.get(other), degree(&board, other));
+            }
+        }
+        while let Some(pos) = placed.pop() {
+            board.remove_piece(pos);
+            degrees.remove(&board, pos);
+            assert_eq!(degrees.get(pos), degree(&board, pos));
+        }
+        assert_eq!(degrees.get((1, 1)), 4);
+    }
+
+    #[test]
+    fn test_degree() {
+        let mut board = self::create_board();
+
+        assert_eq!(degree(&board, (0, 0)), 2);
+        assert_eq!(degree(&board, (1, 0)), 3);
+        assert_eq!(degree(&board, (1, 1)), 4);
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        assert_eq!(degree(&board, (1, 0)), 2);
+        assert_eq!(degree(&board, (2, 1)), 3);
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use board::BoardGame;
+use solver::Solver;
+
+/// Runs a `Solver` on several threads.
+///
+/// The search is split by filling the first `depth` cells of the scan order in every possible
+/// way, see `Solver::split`. Worker threads then take these sub-searches one at a time, each with
+/// its own copy of the board. The workers are done once `ParallelSolver::solve` or
+/// `ParallelSolver::count_solutions` returns.
+#[derive(Debug)]
+pub struct ParallelSolver {
+    solver: Solver,
+    threads: usize,
+    depth: usize,
+}
+
+impl ParallelSolver {
+    /// Creates a parallel search with one thread per available core and a split depth of `3`
+    pub fn new(solver: Solver) -> Self {
+        let threads = thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1);
+
+        Self {
+            solver,
+            threads,
+            depth: 3,
+        }
+    }
+
+    /// Sets the number of worker threads, at least one
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets the number of cells filled to split the search
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Ends the search of every worker once the flag is set, see `Solver::stop_on`.
+    ///
+    /// `ParallelSolver::solve` then returns `None` and `ParallelSolver::count_solutions` the
+    /// solutions counted so far.
+    pub fn stop_on(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.solver = self.solver.stop_on(stop);
+        self
+    }
+
+    /// Returns the first solution found by any worker, the others are stopped
+    pub fn solve(self) -> Option<BoardGame> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let solver = self.solver.stop_on(stop.clone());
+
+        let (receiver, workers) =
+            ParallelSolver::spawn(solver, self.depth, self.threads, |mut part| {
+                part.next_solution()
+            });
+        let solution = receiver.iter().flatten().next();
+        stop.store(true, Ordering::Relaxed);
+        ParallelSolver::join(workers);
+        solution
+    }
+
+    /// Counts the solutions, adding up the counts of every sub-search
+    pub fn count_solutions(self) -> usize {
+        let (receiver, workers) =
+            ParallelSolver::spawn(self.solver, self.depth, self.threads, |mut part| {
+                part.count_solutions()
+            });
+        let count = receiver.iter().sum();
+        ParallelSolver::join(workers);
+        count
+    }
+
+    /// Splits the search and starts the workers, each sending the result of `work` on every
+    /// sub-search it takes
+    fn spawn<T, F>(
+        solver: Solver,
+        depth: usize,
+        threads: usize,
+        work: F,
+    ) -> (mpsc::Receiver<T>, Vec<JoinHandle<()>>)
+    where
+        T: Send + 'static,
+        F: Fn(Solver) -> T + Send + Sync + 'static,
+    {
+        let parts = Arc::new(Mutex::new(solver.split(depth)));
+        let work = Arc::new(work);
+        let (sender, receiver) = mpsc::channel();
+
+        let workers = (0..threads)
+            .map(|_| {
+                let (parts, work, sender) = (parts.clone(), work.clone(), sender.clone());
+                thread::spawn(move || loop {
+                    let part = match parts.lock().unwrap().pop() {
+                        Some(part) => part,
+                        None => return,
+                    };
+                    if sender.send(work(part)).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+
+        (receiver, workers)
+    }
+
+    /// Waits for the workers, passing on their panics
+    fn join(workers: Vec<JoinHandle<()>>) {
+        for worker in workers {
+            if let Err(panic) = worker.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+impl BoardGame {
+    /// Searches a solution of the board on the given number of threads, see `BoardGame::solve`
+    pub fn solve_parallel(&self, threads: usize) -> Option<BoardGame> {
+        ParallelSolver::new(Solver::new(self.clone()))
+            .threads(threads)
+            .solve()
+    }
+
+    /// Counts the solutions of the board on the given number of threads, see
+    /// `BoardGame::count_solutions`
+    pub fn count_solutions_parallel(&self, group_rotations: bool, threads: usize) -> usize {
+        ParallelSolver::new(self.solutions(group_rotations))
+            .threads(threads)
+            .count_solutions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Compass;
+    use fixtures::create_board;
+    use scan::ScanOrder;
+
+    #[test]
+    fn test_solve_parallel() {
+        let board = self::create_board();
+        let solution = board.solve_parallel(4).unwrap();
+
+        assert_eq!(solution.score(), solution.max_score());
+        assert!(solution.placed.iter().all(|placed| *placed));
+    }
+
+    #[test]
+    fn test_solve_parallel_impossible() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+
+        assert!(board.solve_parallel(3).is_none());
+    }
+
+    #[test]
+    fn test_stop_on_parallel() {
+        let board = self::create_board();
+        let stop = Arc::new(AtomicBool::new(true));
+
+        let solver = ParallelSolver::new(Solver::new(board.clone()))
+            .threads(2)
+            .depth(0)
+            .stop_on(stop.clone());
+        assert!(solver.solve().is_none());
+
+        let solver = ParallelSolver::new(Solver::new(board))
+            .threads(2)
+            .depth(0)
+            .stop_on(stop);
+        assert_eq!(solver.count_solutions(), 0);
+    }
+
+    #[test]
+    fn test_count_solutions_parallel() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        assert_eq!(board.count_solutions_parallel(false, 4), all);
+        assert_eq!(board.count_solutions_parallel(true, 2), all / 4);
+        for depth in &[0, 1, 5, 16] {
+            let solver = Solver::with_order(board.clone(), &ScanOrder::Spiral);
+            assert_eq!(
+                ParallelSolver::new(solver)
+                    .threads(3)
+                    .depth(*depth)
+                    .count_solutions(),
+                all
+            );
+        }
+    }
+}
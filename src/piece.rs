@@ -1,3 +1,6 @@
+use std::fmt;
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Piece {
     CornerPiece(Props),
@@ -5,8 +8,26 @@ pub enum Piece {
     FullPiece(Props),
 }
 
+/// Shape of a piece, given by the number of its sides facing the frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    Corner,
+    Border,
+    Full,
+}
+
+impl fmt::Display for PieceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PieceKind::Corner => write!(f, "corner"),
+            PieceKind::Border => write!(f, "border"),
+            PieceKind::Full => write!(f, "full"),
+        }
+    }
+}
+
 impl Piece {
-    pub fn new(id: u8, vec: Vec<u8>) -> Self {
+    pub fn new(id: u16, vec: Vec<u16>) -> Self {
         match vec.as_slice() {
             [0, 0, a, b] if *a > 0 && *b > 0 => {
                 Piece::CornerPiece(Props::new(id, Sides::Corner(*a, *b)))
@@ -21,48 +42,81 @@ impl Piece {
         }
     }
 
+    pub fn get_kind(&self) -> PieceKind {
+        match self {
+            Piece::CornerPiece(_) => PieceKind::Corner,
+            Piece::BorderPiece(_) => PieceKind::Border,
+            Piece::FullPiece(_) => PieceKind::Full,
+        }
+    }
+
+    pub fn get_props(&self) -> Props {
+        match self {
+            Piece::CornerPiece(props) | Piece::BorderPiece(props) | Piece::FullPiece(props) => {
+                *props
+            }
+        }
+    }
+
     pub fn get_faces(&self, offset: &Compass) -> (Face, Face, Face, Face) {
+        self.try_get_faces(offset)
+            .unwrap_or_else(|e| panic!("bad piece <=> props association: {}", e))
+    }
+
+    /// Returns the faces of the piece turned to `offset`.
+    ///
+    /// `Err` if the sides of the piece are not of the same kind as the piece.
+    pub fn try_get_faces(&self, offset: &Compass) -> Result<(Face, Face, Face, Face), FaceError> {
         match self {
             Piece::CornerPiece(Props {
                 kind: Sides::Corner(a, b),
                 ..
-            }) => Sides::get_faces_corner(offset, a, b),
+            }) => Ok(Sides::get_faces_corner(offset, a, b)),
             Piece::BorderPiece(Props {
                 kind: Sides::Border(a, b, c),
                 ..
-            }) => Sides::get_faces_border(offset, a, b, c),
+            }) => Ok(Sides::get_faces_border(offset, a, b, c)),
             Piece::FullPiece(Props {
                 kind: Sides::Full(a, b, c, d),
                 ..
-            }) => Sides::get_faces_full(offset, a, b, c, d),
-            _ => panic!("bad piece <=> props association"),
+            }) => Ok(Sides::get_faces_full(offset, a, b, c, d)),
+            _ => Err(FaceError::KindMismatch),
         }
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Props {
-    pub id: u8,
+    pub id: u16,
     pub kind: Sides,
 }
 
 impl Props {
-    pub fn new(id: u8, kind: Sides) -> Self {
+    pub fn new(id: u16, kind: Sides) -> Self {
         Self { id, kind }
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Sides {
-    Corner(u8, u8),
-    Border(u8, u8, u8),
-    Full(u8, u8, u8, u8),
+    Corner(u16, u16),
+    Border(u16, u16, u16),
+    Full(u16, u16, u16, u16),
 }
 
 use board::Compass;
-use cell::{Border, Face};
+use cell::{Border, Face, FaceError};
 
 impl Sides {
+    /// Returns the four sides as given to `Piece::new`, the sides facing the frame first
+    pub fn values(&self) -> [u16; 4] {
+        match *self {
+            Sides::Corner(a, b) => [0, 0, a, b],
+            Sides::Border(a, b, c) => [0, a, b, c],
+            Sides::Full(a, b, c, d) => [a, b, c, d],
+        }
+    }
+
     pub fn get_corner_offset(borders: &(Border, Border)) -> Compass {
         match borders {
             (Border::North, Border::West) => Compass::East,
@@ -90,7 +144,7 @@ impl Sides {
         }
     }
 
-    pub fn get_faces_corner(offset: &Compass, a: &u8, b: &u8) -> (Face, Face, Face, Face) {
+    pub fn get_faces_corner(offset: &Compass, a: &u16, b: &u16) -> (Face, Face, Face, Face) {
         match offset {
             Compass::North => (Face::Color(*a), Face::Color(*b), Face::Border, Face::Border),
             Compass::East => (Face::Border, Face::Color(*a), Face::Color(*b), Face::Border),
@@ -99,7 +153,12 @@ impl Sides {
         }
     }
 
-    pub fn get_faces_border(offset: &Compass, a: &u8, b: &u8, c: &u8) -> (Face, Face, Face, Face) {
+    pub fn get_faces_border(
+        offset: &Compass,
+        a: &u16,
+        b: &u16,
+        c: &u16,
+    ) -> (Face, Face, Face, Face) {
         match offset {
             Compass::North => (
                 Face::Color(*a),
@@ -128,7 +187,13 @@ impl Sides {
         }
     }
 
-    pub fn get_faces_full(o: &Compass, a: &u8, b: &u8, c: &u8, d: &u8) -> (Face, Face, Face, Face) {
+    pub fn get_faces_full(
+        o: &Compass,
+        a: &u16,
+        b: &u16,
+        c: &u16,
+        d: &u16,
+    ) -> (Face, Face, Face, Face) {
         match o {
             Compass::North => (
                 Face::Color(*a),
@@ -165,7 +230,7 @@ impl Sides {
         }
     }
 
-    pub fn get_face_corner(face: Border, offset: &Compass, a: &u8, b: &u8) -> Face {
+    pub fn get_face_corner(face: Border, offset: &Compass, a: &u16, b: &u16) -> Face {
         match offset {
             Compass::North => match face {
                 Border::North => Face::Color(*a),
@@ -190,7 +255,7 @@ impl Sides {
         }
     }
 
-    pub fn get_face_border(face: Border, offset: &Compass, a: &u8, b: &u8, c: &u8) -> Face {
+    pub fn get_face_border(face: Border, offset: &Compass, a: &u16, b: &u16, c: &u16) -> Face {
         match offset {
             Compass::North => match face {
                 Border::North => Face::Color(*a),
@@ -219,7 +284,14 @@ impl Sides {
         }
     }
 
-    pub fn get_face_full(face: Border, offset: &Compass, a: &u8, b: &u8, c: &u8, d: &u8) -> Face {
+    pub fn get_face_full(
+        face: Border,
+        offset: &Compass,
+        a: &u16,
+        b: &u16,
+        c: &u16,
+        d: &u16,
+    ) -> Face {
         match offset {
             Compass::North => match face {
                 Border::North => Face::Color(*a),
@@ -259,6 +331,39 @@ mod tests {
         Piece::new(1, vec![0, 0, 0, 0]);
     }
 
+    #[test]
+    fn test_try_get_faces() {
+        assert_eq!(
+            Piece::new(1, vec![0, 0, 1, 2]).try_get_faces(&Compass::North),
+            Ok((Face::Color(1), Face::Color(2), Face::Border, Face::Border))
+        );
+        assert_eq!(
+            Piece::CornerPiece(Props::new(1, Sides::Full(1, 2, 3, 4)))
+                .try_get_faces(&Compass::North),
+            Err(FaceError::KindMismatch)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "bad piece <=> props association")]
+    fn test_get_faces_panic() {
+        Piece::BorderPiece(Props::new(1, Sides::Corner(1, 2))).get_faces(&Compass::North);
+    }
+
+    #[test]
+    fn test_sides_values() {
+        for sides in &[vec![0, 0, 1, 2], vec![0, 3, 4, 5], vec![6, 7, 8, 9]] {
+            assert_eq!(
+                Piece::new(0, sides.clone())
+                    .get_props()
+                    .kind
+                    .values()
+                    .to_vec(),
+                *sides
+            );
+        }
+    }
+
     #[test]
     fn test_piece_corner() {
         assert_eq!(
@@ -0,0 +1,484 @@
+use std::collections::VecDeque;
+use std::mem;
+
+use board::{BoardGame, Compass};
+use cell::Face;
+use scan::ScanOrder;
+
+/// Marks an oriented piece missing from the initial domain of a cell
+const ABSENT: u32 = u32::MAX;
+
+/// Feasible oriented pieces of every cell of a board, kept arc consistent.
+///
+/// Each cell holds the `(piece, Compass)` pairs it can still take. A pair stays in a domain only
+/// while every neighbor can show the same color on the shared edge, see `Domains::propagate`, and
+/// its piece is not assigned to another cell. Domains only shrink, `Domains::undo` restores them
+/// as they were before the last `Domains::assign`.
+///
+/// An oriented piece is stored as `4 * id + compass`, each domain is a sparse set: its first
+/// `len` values are feasible, the removed values are swapped behind them. Undoing a removal is
+/// then restoring the length of the domain.
+#[derive(Debug, Clone)]
+pub struct Domains {
+    width: u16,
+    height: u16,
+    /// Colors of the sides of each oriented piece, `0` facing the frame
+    sides: Vec<[u16; 4]>,
+    /// Values of each cell, the first `len` are feasible
+    values: Vec<Vec<u32>>,
+    len: Vec<usize>,
+    /// Index of each value in `values` of each cell, `ABSENT` if the cell never had it
+    places: Vec<Vec<u32>>,
+    /// Cells whose initial domain holds the piece
+    cells_of: Vec<Vec<usize>>,
+    /// Cells and the lengths to give them back, see `Domains::undo`
+    trail: Vec<(usize, usize)>,
+    /// Length of the trail before each assignment
+    levels: Vec<usize>,
+    /// Cells to revise the neighbors of
+    queue: VecDeque<usize>,
+    queued: Vec<bool>,
+    /// Colors shown by a neighbor, one bit per color
+    colors: Vec<u64>,
+}
+
+impl Domains {
+    /// Computes the domains of the cells of the board.
+    ///
+    /// A placed piece is the only value of its cell. An empty cell takes the unplaced pieces of
+    /// its kind, in the orientations matching the frame and its placed neighbors. The domains
+    /// are not propagated yet, see `Domains::propagate`.
+    pub fn new(board: &BoardGame) -> Self {
+        let (width, height) = (board.width, board.height);
+        let size = (width as usize) * (height as usize);
+        let count = board.pieces.len() * 4;
+
+        let face = |face: Face| match face {
+            Face::Color(color) => color,
+            _ => 0,
+        };
+        let sides: Vec<[u16; 4]> = board
+            .pieces
+            .iter()
+            .flat_map(|piece| {
+                Compass::all().to_vec().into_iter().map(move |compass| {
+                    let (a, b, c, d) = piece.get_faces(&compass);
+                    [face(a), face(b), face(c), face(d)]
+                })
+            })
+            .collect();
+        let max_color = sides.iter().flatten().cloned().max().unwrap_or(0) as usize;
+
+        let mut values = Vec::with_capacity(size);
+        for (pos, cell) in board.iter_cells(&ScanOrder::RowMajor) {
+            let domain: Vec<u32> = match cell.get_props() {
+                Some(props) => {
+                    let compass = cell.get_compass().unwrap();
+                    vec![value(props.id, compass)]
+                }
+                None => {
+                    let compasses = match cell.get_kind().get_compass() {
+                        Some(compass) => vec![compass],
+                        None => Compass::all().to_vec(),
+                    };
+                    let (n, e, s, w) = board.get_frontier(pos);
+                    let frontier = [n, e, s, w];
+
+                    board
+                        .pieces
+                        .iter()
+                        .filter(|piece| cell.accepts(piece))
+                        .map(|piece| piece.get_props().id)
+                        .filter(|&id| !board.placed[id as usize])
+                        .flat_map(|id| compasses.iter().map(move |&compass| value(id, compass)))
+                        .filter(|&value| {
+                            sides[value as usize]
+                                .iter()
+                                .zip(&frontier)
+                                .all(|(&side, &frontier)| match frontier {
+                                    Face::None => side != 0,
+                                    frontier => face(frontier) == side,
+                                })
+                        })
+                        .collect()
+                }
+            };
+            values.push(domain);
+        }
+
+        let mut places = vec![vec![ABSENT; count]; size];
+        let mut cells_of = vec![Vec::new(); board.pieces.len()];
+        for (cell, domain) in values.iter().enumerate() {
+            for (place, &value) in domain.iter().enumerate() {
+                places[cell][value as usize] = place as u32;
+                let piece = (value / 4) as usize;
+                if cells_of[piece].last() != Some(&cell) {
+                    cells_of[piece].push(cell);
+                }
+            }
+        }
+
+        Domains {
+            width,
+            height,
+            sides,
+            len: values.iter().map(|domain| domain.len()).collect(),
+            values,
+            places,
+            cells_of,
+            trail: Vec::new(),
+            levels: Vec::new(),
+            queue: VecDeque::new(),
+            queued: vec![false; size],
+            colors: vec![0; max_color / 64 + 1],
+        }
+    }
+
+    /// Returns the number of values left in the domain of the cell
+    pub fn len(&self, pos: (u16, u16)) -> usize {
+        self.len[self.cell(pos)]
+    }
+
+    /// Checks if the domain of the cell is empty, no piece can be placed there anymore
+    pub fn is_empty(&self, pos: (u16, u16)) -> bool {
+        self.len(pos) == 0
+    }
+
+    /// Checks if the oriented piece is still feasible on the cell
+    pub fn contains(&self, pos: (u16, u16), piece: u16, compass: Compass) -> bool {
+        let value = value(piece, compass) as usize;
+        let cell = self.cell(pos);
+        match self.places[cell].get(value) {
+            Some(&place) => place != ABSENT && (place as usize) < self.len[cell],
+            None => false,
+        }
+    }
+
+    /// Iterates over the oriented pieces still feasible on the cell, in no particular order
+    pub fn values(&self, pos: (u16, u16)) -> impl Iterator<Item = (u16, Compass)> + '_ {
+        let cell = self.cell(pos);
+        self.values[cell][..self.len[cell]]
+            .iter()
+            .map(|&value| ((value / 4) as u16, Compass::all()[(value % 4) as usize]))
+    }
+
+    /// Removes the values without support from every domain, until none is left.
+    ///
+    /// # Returns
+    ///
+    /// `false` if a domain is empty, the board cannot be completed.
+    pub fn propagate(&mut self) -> bool {
+        if self.len.contains(&0) {
+            return false;
+        }
+        for cell in 0..self.values.len() {
+            self.enqueue(cell);
+        }
+        self.run()
+    }
+
+    /// Reduces the domain of the cell to the oriented piece, removes the piece from the other
+    /// cells and propagates the change.
+    ///
+    /// Every call opens a level that `Domains::undo` closes, whatever the result.
+    ///
+    /// # Returns
+    ///
+    /// `false` if a domain is empty, the board cannot be completed with this placement.
+    pub fn assign(&mut self, pos: (u16, u16), piece: u16, compass: Compass) -> bool {
+        self.levels.push(self.trail.len());
+        let cell = self.cell(pos);
+        let assigned = value(piece, compass);
+        let place = match self.places[cell].get(assigned as usize) {
+            Some(&place) if place != ABSENT && (place as usize) < self.len[cell] => place,
+            _ => return false,
+        };
+
+        self.swap(cell, place as usize, 0);
+        self.trail.push((cell, self.len[cell]));
+        self.len[cell] = 1;
+        self.enqueue(cell);
+
+        for i in 0..self.cells_of[piece as usize].len() {
+            let other = self.cells_of[piece as usize][i];
+            if other == cell {
+                continue;
+            }
+            let mut changed = false;
+            for compass in 0..4 {
+                changed |= self.remove(other, 4 * piece as u32 + compass);
+            }
+            if changed {
+                if self.len[other] == 0 {
+                    self.clear_queue();
+                    return false;
+                }
+                self.enqueue(other);
+            }
+        }
+
+        self.run()
+    }
+
+    /// Restores the domains as they were before the last `Domains::assign`
+    ///
+    /// # Panics
+    ///
+    /// if there is no assignment to undo
+    pub fn undo(&mut self) {
+        let level = self.levels.pop().expect("no assignment to undo");
+        for (cell, len) in self.trail.drain(level..).rev() {
+            self.len[cell] = len;
+        }
+    }
+
+    /// Returns the number of assignments not undone yet
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    fn cell(&self, pos: (u16, u16)) -> usize {
+        assert!(pos.0 < self.width && pos.1 < self.height);
+        (pos.1 as usize) * (self.width as usize) + pos.0 as usize
+    }
+
+    fn enqueue(&mut self, cell: usize) {
+        if !self.queued[cell] {
+            self.queued[cell] = true;
+            self.queue.push_back(cell);
+        }
+    }
+
+    fn clear_queue(&mut self) {
+        for cell in self.queue.drain(..) {
+            self.queued[cell] = false;
+        }
+    }
+
+    /// Revises the neighbors of the queued cells, AC-3 style
+    fn run(&mut self) -> bool {
+        let width = self.width as usize;
+        while let Some(cell) = self.queue.pop_front() {
+            self.queued[cell] = false;
+            let (x, y) = (cell % width, cell / width);
+
+            // neighbor and the side of the neighbor facing the cell
+            let mut neighbors = [None; 4];
+            if y > 0 {
+                neighbors[0] = Some((cell - width, 2));
+            }
+            if x + 1 < width {
+                neighbors[1] = Some((cell + 1, 3));
+            }
+            if y + 1 < self.height as usize {
+                neighbors[2] = Some((cell + width, 0));
+            }
+            if x > 0 {
+                neighbors[3] = Some((cell - 1, 1));
+            }
+
+            for &(neighbor, side) in neighbors.iter().flatten() {
+                if self.revise(neighbor, side, cell) {
+                    if self.len[neighbor] == 0 {
+                        self.clear_queue();
+                        return false;
+                    }
+                    self.enqueue(neighbor);
+                }
+            }
+        }
+        true
+    }
+
+    /// Removes the values of the cell whose `side` color is not shown by any value of `support`
+    fn revise(&mut self, cell: usize, side: usize, support: usize) -> bool {
+        let opposite = (side + 2) % 4;
+        let mut colors = mem::take(&mut self.colors);
+        for bits in colors.iter_mut() {
+            *bits = 0;
+        }
+        for &value in &self.values[support][..self.len[support]] {
+            let color = self.sides[value as usize][opposite] as usize;
+            colors[color / 64] |= 1 << (color % 64);
+        }
+
+        let mut changed = false;
+        let mut place = self.len[cell];
+        while place > 0 {
+            place -= 1;
+            let value = self.values[cell][place];
+            let color = self.sides[value as usize][side] as usize;
+            if colors[color / 64] & (1 << (color % 64)) == 0 {
+                changed |= self.remove(cell, value);
+            }
+        }
+
+        self.colors = colors;
+        changed
+    }
+
+    /// Removes the value from the domain of the cell, returns `false` if it was not there
+    fn remove(&mut self, cell: usize, value: u32) -> bool {
+        let place = match self.places[cell].get(value as usize) {
+            Some(&place) if place != ABSENT && (place as usize) < self.len[cell] => place as usize,
+            _ => return false,
+        };
+
+        let last = self.len[cell] - 1;
+        self.swap(cell, place, last);
+        self.trail.push((cell, self.len[cell]));
+        self.len[cell] = last;
+        true
+    }
+
+    fn swap(&mut self, cell: usize, a: usize, b: usize) {
+        let values = &mut self.values[cell];
+        values.swap(a, b);
+        self.places[cell][values[a] as usize] = a as u32;
+        self.places[cell][values[b] as usize] = b as u32;
+    }
+}
+
+fn value(piece: u16, compass: Compass) -> u32 {
+    4 * piece as u32 + compass as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::create_board;
+
+    fn sorted(domains: &Domains, pos: (u16, u16)) -> Vec<(u16, Compass)> {
+        let mut values: Vec<_> = domains.values(pos).collect();
+        values.sort_by_key(|&(piece, compass)| value(piece, compass));
+        values
+    }
+
+    #[test]
+    fn test_initial_domains() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        let domains = Domains::new(&board);
+
+        // 4 corner pieces, 8 border pieces, 3 full pieces in 4 orientations
+        assert_eq!(domains.len((0, 0)), 4);
+        assert_eq!(domains.len((2, 0)), 8);
+        // (1, 0) shows the color 3 on its south side
+        assert_eq!(domains.len((1, 0)), 2);
+        assert_eq!(domains.len((2, 2)), 12);
+        assert_eq!(sorted(&domains, (1, 1)), vec![(12, Compass::North)]);
+        assert!(domains.contains((0, 0), 1, Compass::East));
+        assert!(!domains.contains((0, 0), 1, Compass::North));
+        assert!(!domains.contains((2, 2), 12, Compass::North));
+        // (2, 1) shows the color 3 on its west side
+        assert!(domains
+            .values((2, 1))
+            .all(
+                |(piece, compass)| board.pieces[piece as usize].get_faces(&compass).3
+                    == Face::Color(3)
+            ));
+    }
+
+    #[test]
+    fn test_propagate() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        let mut domains = Domains::new(&board);
+        let total = |domains: &Domains| {
+            board
+                .iter_cells(&ScanOrder::RowMajor)
+                .map(|(pos, _)| domains.len(pos))
+                .sum::<usize>()
+        };
+        let before = total(&domains);
+
+        assert!(domains.propagate());
+        assert!(total(&domains) < before);
+        // every value left shows a color on each side that the neighbor can show back
+        let faces = |piece: u16, compass: Compass| {
+            let (n, e, s, w) = board.pieces[piece as usize].get_faces(&compass);
+            [n, e, s, w]
+        };
+        for (pos, _) in board.iter_cells(&ScanOrder::RowMajor) {
+            let neighbors = [
+                (pos.0, pos.1.wrapping_sub(1)),
+                (pos.0 + 1, pos.1),
+                (pos.0, pos.1 + 1),
+                (pos.0.wrapping_sub(1), pos.1),
+            ];
+            for (side, &neighbor) in neighbors.iter().enumerate() {
+                if neighbor.0 >= board.width || neighbor.1 >= board.height {
+                    continue;
+                }
+                for (piece, compass) in domains.values(pos) {
+                    assert!(domains
+                        .values(neighbor)
+                        .any(|(other, turn)| faces(other, turn)[(side + 2) % 4]
+                            == faces(piece, compass)[side]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_propagate_wipeout() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+
+        // the west side of (2, 1) does not match the east side of (1, 1)
+        assert!(!Domains::new(&board).propagate());
+    }
+
+    #[test]
+    fn test_assign_undo() {
+        let board = self::create_board();
+        let mut domains = Domains::new(&board);
+        assert!(domains.propagate());
+        let snapshot: Vec<_> = board
+            .iter_cells(&ScanOrder::RowMajor)
+            .map(|(pos, _)| sorted(&domains, pos))
+            .collect();
+
+        assert!(domains.assign((1, 1), 12, Compass::North));
+        assert_eq!(domains.depth(), 1);
+        assert_eq!(sorted(&domains, (1, 1)), vec![(12, Compass::North)]);
+        assert!(domains.values((2, 2)).all(|(piece, _)| piece != 12));
+        let assigned = domains.len((2, 1));
+
+        assert!(!domains.assign((2, 1), 13, Compass::North));
+        domains.undo();
+        assert_eq!(domains.len((2, 1)), assigned);
+
+        domains.undo();
+        assert_eq!(domains.depth(), 0);
+        let restored: Vec<_> = board
+            .iter_cells(&ScanOrder::RowMajor)
+            .map(|(pos, _)| sorted(&domains, pos))
+            .collect();
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn test_assign_solution() {
+        let board = self::create_board();
+        let solution = board.solve().unwrap();
+        let mut domains = Domains::new(&board);
+        assert!(domains.propagate());
+
+        for (pos, cell) in solution.iter_cells(&ScanOrder::RowMajor) {
+            let id = cell.get_props().unwrap().id;
+            assert!(domains.contains(pos, id, cell.get_compass().unwrap()));
+            assert!(domains.assign(pos, id, cell.get_compass().unwrap()));
+        }
+        assert!(solution
+            .iter_cells(&ScanOrder::RowMajor)
+            .all(|(pos, _)| domains.len(pos) == 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "no assignment to undo")]
+    fn test_undo_panic() {
+        Domains::new(&self::create_board()).undo();
+    }
+}
@@ -0,0 +1,929 @@
+use std::error::Error;
+use std::fmt;
+
+use board::{Compass, PlacementError};
+use cell::{Cell, CellKind};
+use piece::{Piece, PieceKind};
+
+/// Pieces of a puzzle, as read from a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub width: u16,
+    pub height: u16,
+    /// Number of colors, edges are colored from `1` to `colors`
+    pub colors: u16,
+    pub pieces: Vec<Piece>,
+    /// Pieces given with the puzzle, fixed on the board
+    pub hints: Vec<Hint>,
+}
+
+/// Piece fixed at a position and orientation before the search starts
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Hint {
+    pub piece: u16,
+    pub pos: (u16, u16),
+    /// Orientation of a full piece, the frame decides it for corner and border pieces
+    pub compass: Compass,
+}
+
+/// Puzzle file format of this crate.
+///
+/// The file starts with four header lines:
+///
+/// 1. the width and height of the board, or its size for a square board
+/// 2. the number of colors. `0` is the frame, every edge color is between `1` and this number.
+/// 3. and 4. ignored. The piece files this crate started from have these two lines, `1` and
+///    `1 1 1 1` in the samples, but nothing in them describes the puzzle and the first board
+///    loader already skipped them. They are skipped without being read so that those files keep
+///    loading.
+///
+/// Then follows one piece per line, `width * height` pieces in total. A piece is given by the four
+/// colors of its sides, clockwise, starting with the sides facing the frame:
+///
+/// - `0 0 a b` for a corner piece
+/// - `0 a b c` for a border piece
+/// - `a b c d` for a full piece
+///
+/// The id of a piece is its index in the list. Blank lines are ignored.
+///
+/// Hints can follow the last piece, one per line: `hint id x y compass`, the compass being one of
+/// `N`, `E`, `S` or `W`. For example `hint 139 7 8 S` fixes the piece `139` at `(7, 8)`, its
+/// first side facing south.
+pub struct PuzzleFile;
+
+/// Piece files of the Eternity II community benchmarks.
+///
+/// The file has no header, only one piece per line given by its four colors in north, east,
+/// south, west order, `0` being the frame. Pieces can be given in any rotation. The dimensions of
+/// the board are deduced from the number of pieces and of border pieces, the width being the
+/// longest side, and the number of colors from the highest color.
+pub struct BenchmarkFile;
+
+/// Problem found while reading a file, at a given line and column, starting from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// The file ends before the given header line
+    MissingHeader(&'static str),
+    /// The token is not a number in the accepted range
+    InvalidNumber(String),
+    /// A board needs at least two cells on each side to hold its corners
+    InvalidSize(u16),
+    /// The line does not have the expected number of values
+    ValueCount {
+        expected: usize,
+        found: usize,
+    },
+    PieceCount {
+        expected: usize,
+        found: usize,
+    },
+    ColorOutOfRange {
+        color: u16,
+        colors: u16,
+    },
+    /// The sides facing the frame are not first, or the piece has no colored sides
+    InvalidPattern,
+    /// More pieces than ids available
+    TooManyPieces(usize),
+    /// The pieces of a benchmark file do not fill a rectangular board
+    NoDimensions {
+        pieces: usize,
+        borders: usize,
+    },
+    /// The token is not one of `N`, `E`, `S` or `W`
+    InvalidCompass(String),
+    /// The hint cannot be put on the board
+    InvalidHint(PlacementError),
+    /// A piece is given after a hint, hints follow every piece
+    PieceAfterHint,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::MissingHeader(header) => write!(f, "missing {} header", header),
+            ParseErrorKind::InvalidNumber(token) => write!(f, "'{}' is not a valid number", token),
+            ParseErrorKind::InvalidSize(size) => write!(f, "invalid board size {}", size),
+            ParseErrorKind::ValueCount { expected, found } => {
+                write!(f, "expected {} values, found {}", expected, found)
+            }
+            ParseErrorKind::PieceCount { expected, found } => {
+                write!(f, "expected {} pieces, found {}", expected, found)
+            }
+            ParseErrorKind::ColorOutOfRange { color, colors } => write!(
+                f,
+                "color {} is out of range, colors go from 1 to {}",
+                color, colors
+            ),
+            ParseErrorKind::InvalidPattern => write!(
+                f,
+                "invalid piece, sides facing the frame must come first and the other sides be colored"
+            ),
+            ParseErrorKind::TooManyPieces(count) => write!(
+                f,
+                "{} pieces is more than the {} pieces supported",
+                count, MAX_PIECES
+            ),
+            ParseErrorKind::NoDimensions { pieces, borders } => write!(
+                f,
+                "{} pieces with {} border pieces do not fill a rectangular board",
+                pieces, borders
+            ),
+            ParseErrorKind::InvalidCompass(token) => {
+                write!(f, "'{}' is not a compass, expected N, E, S or W", token)
+            }
+            ParseErrorKind::InvalidHint(error) => write!(f, "invalid hint: {}", error),
+            ParseErrorKind::PieceAfterHint => write!(f, "pieces must come before the hints"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Non blank line of a file, split in whitespace separated tokens
+pub(crate) struct Line<'a> {
+    pub(crate) number: usize,
+    pub(crate) tokens: Vec<(usize, &'a str)>,
+}
+
+impl<'a> Line<'a> {
+    pub(crate) fn split(content: &'a str) -> Vec<Line<'a>> {
+        content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| Line {
+                number: i + 1,
+                tokens: Line::tokens(line),
+            })
+            .filter(|line| !line.tokens.is_empty())
+            .collect()
+    }
+
+    /// Returns the tokens of the line with their column
+    fn tokens(line: &str) -> Vec<(usize, &str)> {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (column, (i, c)) in line.char_indices().enumerate() {
+            match (start, c.is_whitespace()) {
+                (None, false) => start = Some((column + 1, i)),
+                (Some((column, begin)), true) => {
+                    tokens.push((column, &line[begin..i]));
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+        if let Some((column, begin)) = start {
+            tokens.push((column, &line[begin..]));
+        }
+        tokens
+    }
+
+    pub(crate) fn error(&self, column: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.number,
+            column,
+            kind,
+        }
+    }
+
+    /// Parses every token of the line, checking their count
+    pub(crate) fn numbers(&self, expected: usize) -> Result<Vec<(usize, u16)>, ParseError> {
+        if self.tokens.len() != expected {
+            let column = self
+                .tokens
+                .get(expected)
+                .or_else(|| self.tokens.last())
+                .map(|(column, _)| *column)
+                .unwrap_or(1);
+
+            return Err(self.error(
+                column,
+                ParseErrorKind::ValueCount {
+                    expected,
+                    found: self.tokens.len(),
+                },
+            ));
+        }
+
+        (0..expected)
+            .map(|i| self.number_at(i).map(|number| (self.tokens[i].0, number)))
+            .collect()
+    }
+
+    /// Parses the token at the given index
+    pub(crate) fn number_at(&self, i: usize) -> Result<u16, ParseError> {
+        let (column, token) = self.tokens[i];
+        token
+            .parse::<u16>()
+            .map_err(|_| self.error(column, ParseErrorKind::InvalidNumber(token.to_string())))
+    }
+
+    pub(crate) fn number(&self) -> Result<u16, ParseError> {
+        self.numbers(1).map(|numbers| numbers[0].1)
+    }
+
+    /// Column of the first token
+    pub(crate) fn start(&self) -> usize {
+        self.tokens[0].0
+    }
+}
+
+/// Number of distinct piece ids
+pub(crate) const MAX_PIECES: usize = u16::MAX as usize + 1;
+
+/// Checks that the sides facing the frame come first and that the other sides are colored
+pub(crate) fn is_canonical(sides: &[u16]) -> bool {
+    match sides {
+        [0, 0, a, b] => *a > 0 && *b > 0,
+        [0, a, b, c] => *a > 0 && *b > 0 && *c > 0,
+        [a, b, c, d] => *a > 0 && *b > 0 && *c > 0 && *d > 0,
+        _ => false,
+    }
+}
+
+impl PuzzleFile {
+    /// Writes the puzzle in the format read by `PuzzleFile::parse`
+    pub fn write(puzzle: &Puzzle) -> String {
+        let mut content = format!(
+            "{} {}\n{}\n1\n1 1 1 1\n",
+            puzzle.width, puzzle.height, puzzle.colors
+        );
+        for piece in &puzzle.pieces {
+            let [a, b, c, d] = piece.get_props().kind.values();
+            content += &format!("{} {} {} {}\n", a, b, c, d);
+        }
+        for hint in &puzzle.hints {
+            content += &format!(
+                "hint {} {} {} {}\n",
+                hint.piece,
+                hint.pos.0,
+                hint.pos.1,
+                hint.compass.letter()
+            );
+        }
+        content
+    }
+
+    /// Reads and checks the content of a puzzle file
+    pub fn parse(content: &str) -> Result<Puzzle, ParseError> {
+        let lines = Line::split(content);
+        let end = content.lines().count() + 1;
+
+        let headers = ["size", "colors", "third", "fourth"];
+        if lines.len() < headers.len() {
+            return Err(ParseError {
+                line: end,
+                column: 1,
+                kind: ParseErrorKind::MissingHeader(headers[lines.len()]),
+            });
+        }
+
+        let dimensions = match lines[0].tokens.len() {
+            1 => lines[0].numbers(1)?,
+            _ => lines[0].numbers(2)?,
+        };
+        for &(column, dimension) in &dimensions {
+            if dimension < 2 {
+                return Err(lines[0].error(column, ParseErrorKind::InvalidSize(dimension)));
+            }
+        }
+        let width = dimensions[0].1;
+        let height = dimensions.last().unwrap().1;
+        let colors = lines[1].number()?;
+
+        let expected = (width as usize) * (height as usize);
+        if expected > MAX_PIECES {
+            return Err(lines[0].error(lines[0].start(), ParseErrorKind::TooManyPieces(expected)));
+        }
+        let body = &lines[headers.len()..];
+        let first_hint = body
+            .iter()
+            .position(|line| line.tokens[0].1 == "hint")
+            .unwrap_or(body.len());
+        let (piece_lines, hint_lines) = body.split_at(first_hint);
+        if let Some(line) = hint_lines.iter().find(|line| line.tokens[0].1 != "hint") {
+            return Err(line.error(line.start(), ParseErrorKind::PieceAfterHint));
+        }
+        let found = piece_lines.len();
+        if found != expected {
+            let error = ParseErrorKind::PieceCount { expected, found };
+            return Err(match piece_lines.get(expected) {
+                Some(line) => line.error(line.start(), error),
+                None => ParseError {
+                    line: end,
+                    column: 1,
+                    kind: error,
+                },
+            });
+        }
+
+        let pieces: Vec<Piece> = piece_lines
+            .iter()
+            .enumerate()
+            .map(|(id, line)| {
+                let numbers = line.numbers(4)?;
+                for &(column, color) in &numbers {
+                    if color > colors {
+                        return Err(
+                            line.error(column, ParseErrorKind::ColorOutOfRange { color, colors })
+                        );
+                    }
+                }
+
+                let sides = numbers.iter().map(|(_, color)| *color).collect::<Vec<_>>();
+                if !is_canonical(&sides) {
+                    return Err(line.error(line.start(), ParseErrorKind::InvalidPattern));
+                }
+
+                Ok(Piece::new(id as u16, sides))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut hints: Vec<Hint> = Vec::with_capacity(hint_lines.len());
+        for line in hint_lines {
+            let hint = PuzzleFile::parse_hint(line)?;
+            let invalid = |token: usize, error| {
+                line.error(line.tokens[token].0, ParseErrorKind::InvalidHint(error))
+            };
+
+            let piece = match pieces.get(hint.piece as usize) {
+                Some(piece) => piece,
+                None => return Err(invalid(1, PlacementError::UnknownPiece { id: hint.piece })),
+            };
+            if hint.pos.0 >= width || hint.pos.1 >= height {
+                return Err(invalid(2, PlacementError::OutOfBounds { pos: hint.pos }));
+            }
+            if hints.iter().any(|other| other.piece == hint.piece) {
+                return Err(invalid(
+                    1,
+                    PlacementError::PieceAlreadyPlaced { id: hint.piece },
+                ));
+            }
+            if hints.iter().any(|other| other.pos == hint.pos) {
+                return Err(invalid(2, PlacementError::CellOccupied { pos: hint.pos }));
+            }
+            let cell = CellKind::at(hint.pos, width, height);
+            if !Cell::empty(cell).accepts(piece) {
+                let error = PlacementError::KindMismatch {
+                    piece: piece.get_kind(),
+                    cell,
+                };
+                return Err(invalid(1, error));
+            }
+
+            hints.push(hint);
+        }
+
+        Ok(Puzzle {
+            width,
+            height,
+            colors,
+            pieces,
+            hints,
+        })
+    }
+
+    /// Reads a `hint id x y compass` line, without checking it against the puzzle
+    fn parse_hint(line: &Line) -> Result<Hint, ParseError> {
+        if line.tokens.len() != 5 {
+            let column = line
+                .tokens
+                .get(5)
+                .or_else(|| line.tokens.last())
+                .map(|(column, _)| *column)
+                .unwrap_or(1);
+
+            return Err(line.error(
+                column,
+                ParseErrorKind::ValueCount {
+                    expected: 5,
+                    found: line.tokens.len(),
+                },
+            ));
+        }
+
+        let (column, token) = line.tokens[4];
+        let compass = Compass::from_letter(token)
+            .ok_or_else(|| line.error(column, ParseErrorKind::InvalidCompass(token.to_string())))?;
+
+        Ok(Hint {
+            piece: line.number_at(1)?,
+            pos: (line.number_at(2)?, line.number_at(3)?),
+            compass,
+        })
+    }
+}
+
+impl BenchmarkFile {
+    /// Reads a benchmark file, turning each piece to start with its sides facing the frame
+    pub fn parse(content: &str) -> Result<Puzzle, ParseError> {
+        let lines = Line::split(content);
+        if lines.len() > MAX_PIECES {
+            let line = &lines[MAX_PIECES];
+            return Err(line.error(line.start(), ParseErrorKind::TooManyPieces(lines.len())));
+        }
+
+        let mut colors = 0;
+        let pieces: Vec<Piece> = lines
+            .iter()
+            .enumerate()
+            .map(|(id, line)| {
+                let mut sides = line
+                    .numbers(4)?
+                    .iter()
+                    .map(|(_, color)| *color)
+                    .collect::<Vec<_>>();
+                colors = sides
+                    .iter()
+                    .fold(colors, |colors, color| colors.max(*color));
+
+                for _ in 0..sides.len() {
+                    if is_canonical(&sides) {
+                        break;
+                    }
+                    sides.rotate_left(1);
+                }
+                if !is_canonical(&sides) {
+                    return Err(line.error(line.start(), ParseErrorKind::InvalidPattern));
+                }
+
+                Ok(Piece::new(id as u16, sides))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let count = |kind| {
+            pieces
+                .iter()
+                .filter(|piece| piece.get_kind() == kind)
+                .count()
+        };
+        let (corners, borders) = (count(PieceKind::Corner), count(PieceKind::Border));
+        let dimensions = (2..=u16::MAX)
+            .take_while(|height| (*height as usize) * (*height as usize) <= pieces.len())
+            .filter(|height| pieces.len().is_multiple_of(*height as usize))
+            .map(|height| (pieces.len() / (height as usize), height as usize))
+            .find(|&(width, height)| corners == 4 && borders == 2 * (width + height) - 8);
+
+        let (width, height) = match dimensions {
+            Some((width, height)) if width <= u16::MAX as usize => (width as u16, height as u16),
+            _ => {
+                return Err(ParseError {
+                    line: content.lines().count() + 1,
+                    column: 1,
+                    kind: ParseErrorKind::NoDimensions {
+                        pieces: pieces.len(),
+                        borders,
+                    },
+                })
+            }
+        };
+
+        Ok(Puzzle {
+            width,
+            height,
+            colors,
+            pieces,
+            hints: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piece::Sides;
+
+    const PUZZLE: &str = "2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 0 1 1\n0 0 2 3\n";
+
+    fn error(content: &str) -> (usize, usize, ParseErrorKind) {
+        let error = PuzzleFile::parse(content).unwrap_err();
+        (error.line, error.column, error.kind)
+    }
+
+    #[test]
+    fn test_parse() {
+        let puzzle = PuzzleFile::parse(PUZZLE).unwrap();
+
+        assert_eq!((puzzle.width, puzzle.height), (2, 2));
+        assert_eq!(puzzle.colors, 3);
+        assert_eq!(
+            puzzle.pieces,
+            vec![
+                Piece::new(0, vec![0, 0, 1, 2]),
+                Piece::new(1, vec![0, 0, 2, 1]),
+                Piece::new(2, vec![0, 0, 1, 1]),
+                Piece::new(3, vec![0, 0, 2, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_pieces_4x4() {
+        let puzzle = PuzzleFile::parse(include_str!("../pieces_4x4.txt")).unwrap();
+
+        assert_eq!((puzzle.width, puzzle.height), (4, 4));
+        assert_eq!(puzzle.pieces.len(), 16);
+    }
+
+    #[test]
+    fn test_parse_rectangular() {
+        let content = "3 2\n3\n1\n1 1 1 1\n0 0 1 1\n0 2 3 1\n0 0 2 2\n0 0 1 1\n0 1 3 2\n0 0 2 2\n";
+        let puzzle = PuzzleFile::parse(content).unwrap();
+
+        assert_eq!((puzzle.width, puzzle.height), (3, 2));
+        assert_eq!(puzzle.pieces.len(), 6);
+
+        assert_eq!(
+            error("3 2\n3\n1\n1 1 1 1\n0 0 1 1\n0 2 3 1\n0 0 2 2\n0 0 1 1\n"),
+            (
+                9,
+                1,
+                ParseErrorKind::PieceCount {
+                    expected: 6,
+                    found: 4
+                }
+            )
+        );
+        assert_eq!(
+            error("3 1\n3\n1\n1 1 1 1\n"),
+            (1, 3, ParseErrorKind::InvalidSize(1))
+        );
+        assert_eq!(
+            error("3 2 1\n3\n1\n1 1 1 1\n").2,
+            ParseErrorKind::ValueCount {
+                expected: 2,
+                found: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_large_values() {
+        let content = "2\n300\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 0 1 1\n0 0 2 300\n";
+        let puzzle = PuzzleFile::parse(content).unwrap();
+        assert_eq!(puzzle.colors, 300);
+        assert_eq!(puzzle.pieces[3], Piece::new(3, vec![0, 0, 2, 300]));
+
+        assert_eq!(
+            error("2\n70000\n1\n1 1 1 1\n"),
+            (2, 1, ParseErrorKind::InvalidNumber("70000".to_string()))
+        );
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 0 1 1\n0 0 2 65536\n"),
+            (8, 7, ParseErrorKind::InvalidNumber("65536".to_string()))
+        );
+        assert_eq!(
+            error("300 300\n3\n1\n1 1 1 1\n"),
+            (1, 1, ParseErrorKind::TooManyPieces(90000))
+        );
+    }
+
+    #[test]
+    fn test_parse_blank_lines() {
+        let content = "\n2\n3\n\n1\n1 1 1 1\n0 0 1 2\n  0 0 2 1\n0 0 1 1\n\n0 0 2 3\n\n";
+        assert_eq!(
+            PuzzleFile::parse(content).unwrap(),
+            PuzzleFile::parse(PUZZLE).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_skipped_headers() {
+        let content = PUZZLE.replacen("1\n1 1 1 1\n", "2\nanything\n", 1);
+        assert_ne!(content, PUZZLE);
+        assert_eq!(PuzzleFile::parse(&content), PuzzleFile::parse(PUZZLE));
+    }
+
+    #[test]
+    fn test_parse_header_errors() {
+        assert_eq!(
+            error("2\n3\n"),
+            (3, 1, ParseErrorKind::MissingHeader("third"))
+        );
+        assert_eq!(
+            error("x\n3\n1\n1 1 1 1\n"),
+            (1, 1, ParseErrorKind::InvalidNumber("x".to_string()))
+        );
+        assert_eq!(
+            error("1\n3\n1\n1 1 1 1\n0 0 1 1\n"),
+            (1, 1, ParseErrorKind::InvalidSize(1))
+        );
+        assert_eq!(
+            error("2\n3 4\n1\n1 1 1 1\n"),
+            (
+                2,
+                3,
+                ParseErrorKind::ValueCount {
+                    expected: 1,
+                    found: 2
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_piece_count() {
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 0 1 1\n"),
+            (
+                8,
+                1,
+                ParseErrorKind::PieceCount {
+                    expected: 4,
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(
+            error(&format!("{}0 0 1 1\n", PUZZLE)),
+            (
+                9,
+                1,
+                ParseErrorKind::PieceCount {
+                    expected: 4,
+                    found: 5
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_piece_errors() {
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2\n0 0 1 1\n0 0 2 3\n"),
+            (
+                6,
+                5,
+                ParseErrorKind::ValueCount {
+                    expected: 4,
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 0 1 1 1\n0 0 2 3\n"),
+            (
+                7,
+                9,
+                ParseErrorKind::ValueCount {
+                    expected: 4,
+                    found: 5
+                }
+            )
+        );
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0  0 1 -1\n0 0 2 3\n"),
+            (7, 8, ParseErrorKind::InvalidNumber("-1".to_string()))
+        );
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 0 1 1\n0 0 2 4\n"),
+            (
+                8,
+                7,
+                ParseErrorKind::ColorOutOfRange {
+                    color: 4,
+                    colors: 3
+                }
+            )
+        );
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 2 0 1\n0 0 1 1\n0 0 2 3\n"),
+            (6, 1, ParseErrorKind::InvalidPattern)
+        );
+        assert_eq!(
+            error("2\n3\n1\n1 1 1 1\n0 0 0 0\n0 0 2 1\n0 0 1 1\n0 0 2 3\n"),
+            (5, 1, ParseErrorKind::InvalidPattern)
+        );
+    }
+
+    #[test]
+    fn test_parse_hints() {
+        let puzzle =
+            PuzzleFile::parse(&format!("{}hint 2 1 0 N\n\nhint 0 0 1 W\n", PUZZLE)).unwrap();
+
+        assert_eq!(puzzle.pieces.len(), 4);
+        assert_eq!(
+            puzzle.hints,
+            vec![
+                Hint {
+                    piece: 2,
+                    pos: (1, 0),
+                    compass: Compass::North
+                },
+                Hint {
+                    piece: 0,
+                    pos: (0, 1),
+                    compass: Compass::West
+                },
+            ]
+        );
+        assert_eq!(PuzzleFile::parse(PUZZLE).unwrap().hints, vec![]);
+    }
+
+    #[test]
+    fn test_parse_hint_errors() {
+        assert_eq!(
+            error(&PUZZLE.replacen("0 0 2 3\n", "hint 2 1 0 N\n0 0 2 3\n", 1)),
+            (9, 1, ParseErrorKind::PieceAfterHint)
+        );
+        assert_eq!(
+            error(&format!("{}hint 2 1 0\n", PUZZLE)),
+            (
+                9,
+                10,
+                ParseErrorKind::ValueCount {
+                    expected: 5,
+                    found: 4
+                }
+            )
+        );
+        assert_eq!(
+            error(&format!("{}hint 2 1 0 X\n", PUZZLE)),
+            (9, 12, ParseErrorKind::InvalidCompass("X".to_string()))
+        );
+        assert_eq!(
+            error(&format!("{}hint 2 a 0 N\n", PUZZLE)),
+            (9, 8, ParseErrorKind::InvalidNumber("a".to_string()))
+        );
+        assert_eq!(
+            error(&format!("{}hint 4 1 0 N\n", PUZZLE)),
+            (
+                9,
+                6,
+                ParseErrorKind::InvalidHint(PlacementError::UnknownPiece { id: 4 })
+            )
+        );
+        assert_eq!(
+            error(&format!("{}hint 2 1 2 N\n", PUZZLE)),
+            (
+                9,
+                8,
+                ParseErrorKind::InvalidHint(PlacementError::OutOfBounds { pos: (1, 2) })
+            )
+        );
+        assert_eq!(
+            error(&format!("{}hint 2 1 0 N\nhint 2 0 0 N\n", PUZZLE)),
+            (
+                10,
+                6,
+                ParseErrorKind::InvalidHint(PlacementError::PieceAlreadyPlaced { id: 2 })
+            )
+        );
+        assert_eq!(
+            error(&format!("{}hint 2 1 0 N\nhint 1 1 0 N\n", PUZZLE)),
+            (
+                10,
+                8,
+                ParseErrorKind::InvalidHint(PlacementError::CellOccupied { pos: (1, 0) })
+            )
+        );
+
+        let content = "3\n5\n1\n1 1 1 1\n0 0 1 1\n0 0 1 2\n0 0 2 1\n0 0 2 2\n0 1 3 1\n0 1 3 2\n0 1 4 1\n0 1 5 2\n3 3 5 5\nhint 8 0 1 N\n";
+        assert_eq!(
+            error(content),
+            (
+                14,
+                6,
+                ParseErrorKind::InvalidHint(PlacementError::KindMismatch {
+                    piece: PieceKind::Full,
+                    cell: CellKind::Border(::cell::Border::West)
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let mut puzzle = PuzzleFile::parse(include_str!("../pieces_4x4.txt")).unwrap();
+        puzzle.hints.push(Hint {
+            piece: 12,
+            pos: (1, 2),
+            compass: Compass::East,
+        });
+        let content = PuzzleFile::write(&puzzle);
+
+        assert!(content.starts_with("4 4\n5\n1\n1 1 1 1\n0 0 1 1\n"));
+        assert!(content.ends_with("3 5 5 4\nhint 12 1 2 E\n"));
+        assert_eq!(PuzzleFile::parse(&content), Ok(puzzle));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = PuzzleFile::parse("2\n3\n1\n1 1 1 1\n0 0 1 2\n0 0 2 1\n0 0 1 1\n0 0 2 4\n")
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "line 8, column 7: color 4 is out of range, colors go from 1 to 3"
+        );
+    }
+
+    #[test]
+    fn test_parse_benchmark() {
+        let content = "1 2 0 0\n0 1 1 0\n1 0 0 2\n0 0 2 2\n";
+        let puzzle = BenchmarkFile::parse(content).unwrap();
+
+        assert_eq!((puzzle.width, puzzle.height), (2, 2));
+        assert_eq!(puzzle.colors, 2);
+        assert_eq!(
+            puzzle.pieces,
+            vec![
+                Piece::new(0, vec![0, 0, 1, 2]),
+                Piece::new(1, vec![0, 0, 1, 1]),
+                Piece::new(2, vec![0, 0, 2, 1]),
+                Piece::new(3, vec![0, 0, 2, 2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_benchmark_rotations() {
+        let native = PuzzleFile::parse(include_str!("../pieces_4x4.txt")).unwrap();
+        let content = native
+            .pieces
+            .iter()
+            .enumerate()
+            .map(|(i, piece)| {
+                let mut sides = match piece.get_props().kind {
+                    Sides::Corner(a, b) => vec![0, 0, a, b],
+                    Sides::Border(a, b, c) => vec![0, a, b, c],
+                    Sides::Full(a, b, c, d) => vec![a, b, c, d],
+                };
+                sides.rotate_right(i % 4);
+                sides
+                    .iter()
+                    .map(|color| color.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let puzzle = BenchmarkFile::parse(&content).unwrap();
+        assert_eq!(puzzle.width, native.width);
+        assert_eq!(puzzle.height, native.height);
+        assert_eq!(puzzle.colors, native.colors);
+        assert_eq!(puzzle.pieces.len(), native.pieces.len());
+        for (piece, expected) in puzzle.pieces.iter().zip(native.pieces.iter()) {
+            assert_eq!(piece.get_kind(), expected.get_kind());
+            let faces = Compass::all()
+                .iter()
+                .map(|compass| expected.get_faces(compass))
+                .collect::<Vec<_>>();
+            assert!(faces.contains(&piece.get_faces(&Compass::North)));
+        }
+    }
+
+    #[test]
+    fn test_parse_benchmark_rectangular() {
+        let content = "0 0 1 1\n0 2 3 1\n2 2 0 0\n0 0 1 1\n3 2 0 1\n0 0 2 2\n";
+        let puzzle = BenchmarkFile::parse(content).unwrap();
+
+        assert_eq!((puzzle.width, puzzle.height), (3, 2));
+        assert_eq!(puzzle.pieces[2], Piece::new(2, vec![0, 0, 2, 2]));
+        assert_eq!(puzzle.pieces[4], Piece::new(4, vec![0, 1, 3, 2]));
+    }
+
+    #[test]
+    fn test_parse_benchmark_too_many_pieces() {
+        let content = "1 2 3 4\n".repeat(MAX_PIECES + 1);
+        let error = BenchmarkFile::parse(&content).unwrap_err();
+
+        assert_eq!(
+            (error.line, error.kind),
+            (
+                MAX_PIECES + 1,
+                ParseErrorKind::TooManyPieces(MAX_PIECES + 1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_benchmark_errors() {
+        assert_eq!(
+            BenchmarkFile::parse("1 2 0 0\n0 1 1 0\n2 0 0 1\n")
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::NoDimensions {
+                pieces: 3,
+                borders: 0
+            }
+        );
+        let error = BenchmarkFile::parse("1 2 0 0\n0 1 0 1\n2 0 0 1\n0 0 2 2\n").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.kind),
+            (2, 1, ParseErrorKind::InvalidPattern)
+        );
+        let error = BenchmarkFile::parse("1 2 0 0\n0 1 1\n2 0 0 1\n0 0 2 2\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 5));
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use board::{BoardGame, Compass};
+use cell::{Border, Cell, Face};
+
+/// Background colors of the 256 color terminal palette, cycled through by color id
+const PALETTE: [u8; 24] = [
+    33, 40, 214, 129, 51, 226, 201, 28, 94, 63, 172, 37, 99, 148, 208, 24, 165, 70, 136, 31, 219,
+    58, 105, 180,
+];
+
+const RESET: &str = "\x1b[0m";
+const FRAME: &str = "\x1b[38;5;231;48;5;244m";
+const MISMATCH: &str = "\x1b[1;38;5;231;41m";
+
+/// Text drawing of a board, see `BoardGame::render`.
+///
+/// Each cell is a tile showing the color of each side, `#` for the sides facing the frame:
+///
+/// ```text
+/// +--##--+ +......+
+/// |##  01| .      .
+/// +--02--+ +......+
+/// ```
+///
+/// Empty cells are dotted.
+pub struct Render<'a> {
+    board: &'a BoardGame,
+    color: bool,
+}
+
+impl BoardGame {
+    /// Returns a drawing of the board for the terminal.
+    ///
+    /// With `color`, each color is drawn with its own ANSI background and the mismatching sides,
+    /// see `BoardGame::mismatches`, are drawn on red.
+    pub fn render(&self, color: bool) -> Render<'_> {
+        Render { board: self, color }
+    }
+}
+
+impl fmt::Display for BoardGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render(false).fmt(f)
+    }
+}
+
+impl<'a> Render<'a> {
+    /// Returns the number of digits used for each color
+    fn digits(&self) -> usize {
+        let colors = self
+            .board
+            .pieces
+            .iter()
+            .flat_map(|piece| {
+                let (a, b, c, d) = piece.get_faces(&Compass::North);
+                vec![a, b, c, d]
+            })
+            .filter_map(|face| match face {
+                Face::Color(color) => Some(color),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        colors.to_string().len().max(2)
+    }
+
+    /// Writes the face with its colors
+    fn face(
+        &self,
+        f: &mut fmt::Formatter,
+        face: Face,
+        mismatch: bool,
+        digits: usize,
+    ) -> fmt::Result {
+        let text = match face {
+            Face::Color(color) => format!("{:0width$}", color, width = digits),
+            Face::Border => "#".repeat(digits),
+            Face::None => " ".repeat(digits),
+        };
+        if !self.color {
+            return write!(f, "{}", text);
+        }
+
+        match face {
+            _ if mismatch => write!(f, "{}{}{}", MISMATCH, text, RESET),
+            Face::Color(color) => write!(
+                f,
+                "\x1b[38;5;16;48;5;{}m{}{}",
+                PALETTE[(color as usize - 1) % PALETTE.len()],
+                text,
+                RESET
+            ),
+            Face::Border => write!(f, "{}{}{}", FRAME, text, RESET),
+            Face::None => write!(f, "{}", text),
+        }
+    }
+
+    /// Writes one of the three lines of a tile
+    fn tile_line(
+        &self,
+        f: &mut fmt::Formatter,
+        pos: (u16, u16),
+        line: usize,
+        mismatches: &HashSet<((u16, u16), Border)>,
+        digits: usize,
+    ) -> fmt::Result {
+        let (x, y) = pos;
+        let cell: &Cell = &self.board.cells[y as usize][x as usize];
+        let inner = digits + 2;
+        let (left, right) = (inner / 2, inner - inner / 2);
+        let face = |side: Border| cell.get_face(side);
+        let mismatch = |side: Border| mismatches.contains(&(pos, side));
+
+        if cell.is_empty() {
+            return match line {
+                1 => write!(f, ".{}.", " ".repeat(2 * digits + 2)),
+                _ => write!(f, "+{}+", ".".repeat(2 * digits + 2)),
+            };
+        }
+
+        match line {
+            0 | 2 => {
+                let side = if line == 0 {
+                    Border::North
+                } else {
+                    Border::South
+                };
+                write!(f, "+{}", "-".repeat(left))?;
+                self.face(f, face(side), mismatch(side), digits)?;
+                write!(f, "{}+", "-".repeat(right))
+            }
+            _ => {
+                write!(f, "|")?;
+                self.face(f, face(Border::West), mismatch(Border::West), digits)?;
+                write!(f, "  ")?;
+                self.face(f, face(Border::East), mismatch(Border::East), digits)?;
+                write!(f, "|")
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Display for Render<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.digits();
+        let mismatches: HashSet<_> = if self.color {
+            self.board.mismatches().into_iter().collect()
+        } else {
+            HashSet::new()
+        };
+
+        for y in 0..self.board.height {
+            for line in 0..3 {
+                for x in 0..self.board.width {
+                    if x > 0 {
+                        write!(f, " ")?;
+                    }
+                    self.tile_line(f, (x, y), line, &mismatches, digits)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_board() -> BoardGame {
+        let content = "3 2\n3\n1\n1 1 1 1\n0 0 1 1\n0 2 3 1\n0 0 2 2\n0 0 1 1\n0 1 3 2\n0 0 2 2";
+
+        BoardGame::new(content.lines().map(|line| line.to_string()).collect())
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let board = self::create_board();
+
+        assert_eq!(
+            board.to_string(),
+            "+......+ +......+ +......+\n\
+             .      . .      . .      .\n\
+             +......+ +......+ +......+\n\
+             +......+ +......+ +......+\n\
+             .      . .      . .      .\n\
+             +......+ +......+ +......+\n"
+        );
+    }
+
+    #[test]
+    fn test_render_pieces() {
+        let mut board = self::create_board();
+        board.put_piece(0, (0, 0), None).unwrap();
+        board.put_piece(1, (1, 0), None).unwrap();
+        board.put_piece(3, (0, 1), None).unwrap();
+
+        assert_eq!(
+            board.to_string(),
+            "+--##--+ +--##--+ +......+\n\
+             |##  01| |01  02| .      .\n\
+             +--01--+ +--03--+ +......+\n\
+             +--01--+ +......+ +......+\n\
+             |##  01| .      . .      .\n\
+             +--##--+ +......+ +......+\n"
+        );
+    }
+
+    #[test]
+    fn test_render_color() {
+        let mut board = self::create_board();
+        board.put_piece(0, (0, 0), None).unwrap();
+        board.put_piece(3, (0, 1), None).unwrap();
+
+        let color = board.render(true).to_string();
+        assert!(color.starts_with(&format!("+--{}##{}--+", FRAME, RESET)));
+        assert!(color.contains("  \x1b[38;5;16;48;5;33m01\x1b[0m|"));
+        assert!(!color.contains(MISMATCH));
+        assert!(!board.render(false).to_string().contains('\x1b'));
+
+        board.remove_piece((0, 1));
+        board.put_piece(2, (0, 1), None).unwrap();
+        assert_eq!(board.mismatches().len(), 2);
+        let color = board.render(true).to_string();
+        assert_eq!(color.matches(MISMATCH).count(), 2);
+        assert!(color.contains(&format!("+--{}01{}--+", MISMATCH, RESET)));
+        assert!(color.contains(&format!("+--{}02{}--+", MISMATCH, RESET)));
+    }
+}
@@ -0,0 +1,201 @@
+/// Order in which the cells of a board are visited
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ScanOrder {
+    /// Rows from north to south, each row from west to east
+    #[default]
+    RowMajor,
+    /// Columns from west to east, each column from north to south
+    ColumnMajor,
+    /// Clockwise rings from the frame inward, each starting at its north-west corner
+    Spiral,
+    /// The frame as a clockwise ring, then the inner cells in row-major order
+    BorderFirst,
+    /// Anti-diagonals from the north-west corner, each from north-east to south-west
+    Diagonal,
+    /// Given `(x, y)` positions.
+    ///
+    /// Positions outside the board and repeated positions are skipped, the cells missing from
+    /// the list are visited afterwards in row-major order.
+    Custom(Vec<(u16, u16)>),
+}
+
+impl ScanOrder {
+    /// Returns every position of a board of the given dimensions, in this order
+    pub fn positions(&self, width: u16, height: u16) -> Vec<(u16, u16)> {
+        if width == 0 || height == 0 {
+            return Vec::new();
+        }
+
+        match self {
+            ScanOrder::RowMajor => ScanOrder::row_major(width, height),
+            ScanOrder::ColumnMajor => (0..width)
+                .flat_map(|x| (0..height).map(move |y| (x, y)))
+                .collect(),
+            ScanOrder::Spiral => (0..width.min(height).div_ceil(2))
+                .flat_map(|ring| ScanOrder::ring(width, height, ring))
+                .collect(),
+            ScanOrder::BorderFirst => {
+                let mut positions = ScanOrder::ring(width, height, 0);
+                positions.extend(
+                    ScanOrder::row_major(width, height)
+                        .into_iter()
+                        .filter(|&(x, y)| x > 0 && y > 0 && x < width - 1 && y < height - 1),
+                );
+                positions
+            }
+            ScanOrder::Diagonal => {
+                let (width, height) = (width as usize, height as usize);
+                let mut positions = Vec::with_capacity(width * height);
+                for diagonal in 0..width + height - 1 {
+                    for y in diagonal.saturating_sub(width - 1)..=diagonal.min(height - 1) {
+                        positions.push(((diagonal - y) as u16, y as u16));
+                    }
+                }
+                positions
+            }
+            ScanOrder::Custom(custom) => {
+                let all = ScanOrder::row_major(width, height);
+                let (width, height) = (width as usize, height as usize);
+                let mut seen = vec![false; width * height];
+                let mut positions = Vec::with_capacity(width * height);
+                for &(x, y) in custom.iter().chain(all.iter()) {
+                    let (i, j) = (x as usize, y as usize);
+                    if i < width && j < height && !seen[j * width + i] {
+                        seen[j * width + i] = true;
+                        positions.push((x, y));
+                    }
+                }
+                positions
+            }
+        }
+    }
+
+    fn row_major(width: u16, height: u16) -> Vec<(u16, u16)> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .collect()
+    }
+
+    /// Returns the cells at distance `ring` of the frame, clockwise from the north-west corner
+    fn ring(width: u16, height: u16, ring: u16) -> Vec<(u16, u16)> {
+        let (west, east) = (ring, width - 1 - ring);
+        let (north, south) = (ring, height - 1 - ring);
+
+        let mut positions = Vec::new();
+        positions.extend((west..=east).map(|x| (x, north)));
+        positions.extend((north + 1..=south).map(|y| (east, y)));
+        if south > north {
+            positions.extend((west..east).rev().map(|x| (x, south)));
+        }
+        if east > west {
+            positions.extend((north + 1..south).rev().map(|y| (west, y)));
+        }
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_permutations(order: &ScanOrder) {
+        for width in 1..7 {
+            for height in 1..7 {
+                let mut sorted = order.positions(width, height);
+                sorted.sort();
+                sorted.dedup();
+                assert_eq!(sorted.len(), (width as usize) * (height as usize));
+                assert!(sorted.iter().all(|&(x, y)| x < width && y < height));
+            }
+        }
+    }
+
+    #[test]
+    fn test_row_column_major() {
+        assert_eq!(
+            ScanOrder::RowMajor.positions(2, 2),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(
+            ScanOrder::ColumnMajor.positions(2, 2),
+            vec![(0, 0), (0, 1), (1, 0), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_spiral() {
+        assert_eq!(
+            ScanOrder::Spiral.positions(3, 3),
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (2, 1),
+                (2, 2),
+                (1, 2),
+                (0, 2),
+                (0, 1),
+                (1, 1)
+            ]
+        );
+        assert_permutations(&ScanOrder::Spiral);
+    }
+
+    #[test]
+    fn test_border_first() {
+        let positions = ScanOrder::BorderFirst.positions(4, 4);
+
+        assert_eq!(positions[..12], ScanOrder::Spiral.positions(4, 4)[..12]);
+        assert_eq!(positions[12..], [(1, 1), (2, 1), (1, 2), (2, 2)]);
+        assert_permutations(&ScanOrder::BorderFirst);
+    }
+
+    #[test]
+    fn test_diagonal() {
+        assert_eq!(
+            ScanOrder::Diagonal.positions(3, 3),
+            vec![
+                (0, 0),
+                (1, 0),
+                (0, 1),
+                (2, 0),
+                (1, 1),
+                (0, 2),
+                (2, 1),
+                (1, 2),
+                (2, 2)
+            ]
+        );
+        assert_permutations(&ScanOrder::Diagonal);
+    }
+
+    #[test]
+    fn test_rectangular() {
+        assert_eq!(
+            ScanOrder::Spiral.positions(3, 2),
+            vec![(0, 0), (1, 0), (2, 0), (2, 1), (1, 1), (0, 1)]
+        );
+        assert_eq!(
+            ScanOrder::Spiral.positions(1, 3),
+            vec![(0, 0), (0, 1), (0, 2)]
+        );
+        assert_eq!(
+            ScanOrder::Diagonal.positions(3, 2),
+            vec![(0, 0), (1, 0), (0, 1), (2, 0), (1, 1), (2, 1)]
+        );
+        assert_eq!(
+            ScanOrder::ColumnMajor.positions(2, 3),
+            vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]
+        );
+        assert_permutations(&ScanOrder::RowMajor);
+        assert_permutations(&ScanOrder::ColumnMajor);
+        assert_permutations(&ScanOrder::Custom(vec![(2, 0), (0, 2)]));
+    }
+
+    #[test]
+    fn test_custom() {
+        let order = ScanOrder::Custom(vec![(1, 1), (5, 0), (1, 1), (0, 1)]);
+
+        assert_eq!(order.positions(2, 2), vec![(1, 1), (0, 1), (0, 0), (1, 0)]);
+    }
+}
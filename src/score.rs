@@ -0,0 +1,188 @@
+use board::BoardGame;
+use cell::{Border, Face};
+
+impl BoardGame {
+    /// Counts the internal edges whose two sides are filled and show the same color.
+    ///
+    /// Edges facing the frame are not counted. Pieces put with `BoardGame::put_piece` are scored
+    /// as any other, a complete solution scores `BoardGame::max_score`.
+    pub fn score(&self) -> u32 {
+        let mut score = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if x + 1 < self.width && self.edge_matches((x, y), Border::East) {
+                    score += 1;
+                }
+                if y + 1 < self.height && self.edge_matches((x, y), Border::South) {
+                    score += 1;
+                }
+            }
+        }
+        score
+    }
+
+    /// Counts the matched edges with at least one side on the given distinct positions.
+    ///
+    /// The difference of this score before and after changing these cells is the difference of
+    /// `BoardGame::score`.
+    pub fn score_around(&self, positions: &[(u16, u16)]) -> u32 {
+        let mut score = 0;
+        for &pos in positions {
+            for side in &[Border::North, Border::East, Border::South, Border::West] {
+                let neighbor = match self.neighbor(pos, *side) {
+                    Some(neighbor) => neighbor,
+                    None => continue,
+                };
+                // edges inside the positions are counted from their first cell only
+                if positions.contains(&neighbor) && (neighbor.1, neighbor.0) < (pos.1, pos.0) {
+                    continue;
+                }
+                if self.edge_matches(pos, *side) {
+                    score += 1;
+                }
+            }
+        }
+        score
+    }
+
+    /// Returns the number of internal edges of the board
+    pub fn max_score(&self) -> u32 {
+        let (width, height) = (self.width as u32, self.height as u32);
+        (width - 1) * height + width * (height - 1)
+    }
+
+    /// Lists the sides of the placed pieces showing another color than their placed neighbor.
+    ///
+    /// Each mismatching edge appears twice, once from each of its cells, in row-major order and
+    /// clockwise from north for a cell.
+    pub fn mismatches(&self) -> Vec<((u16, u16), Border)> {
+        let mut mismatches = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for side in &[Border::North, Border::East, Border::South, Border::West] {
+                    let neighbor = match self.neighbor((x, y), *side) {
+                        Some(neighbor) => neighbor,
+                        None => continue,
+                    };
+                    let (face, other) = self.edge((x, y), neighbor, *side);
+                    if face != Face::None && other != Face::None && face != other {
+                        mismatches.push(((x, y), *side));
+                    }
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// Checks if the edge on the given side of the position is filled on both sides and matches
+    fn edge_matches(&self, pos: (u16, u16), side: Border) -> bool {
+        match self.neighbor(pos, side) {
+            Some(neighbor) => {
+                let (face, other) = self.edge(pos, neighbor, side);
+                face != Face::None && face == other
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the faces of an edge, seen from the position and from its neighbor
+    fn edge(&self, pos: (u16, u16), neighbor: (u16, u16), side: Border) -> (Face, Face) {
+        let opposite = match side {
+            Border::North => Border::South,
+            Border::East => Border::West,
+            Border::South => Border::North,
+            Border::West => Border::East,
+        };
+        (
+            self.cells[pos.1 as usize][pos.0 as usize].get_face(side),
+            self.cells[neighbor.1 as usize][neighbor.0 as usize].get_face(opposite),
+        )
+    }
+
+    /// Returns the position next to the given one on this side, `None` outside the board
+    fn neighbor(&self, pos: (u16, u16), side: Border) -> Option<(u16, u16)> {
+        let (x, y) = pos;
+        match side {
+            Border::North if y > 0 => Some((x, y - 1)),
+            Border::East if x + 1 < self.width => Some((x + 1, y)),
+            Border::South if y + 1 < self.height => Some((x, y + 1)),
+            Border::West if x > 0 => Some((x - 1, y)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Compass;
+    use fixtures::create_board;
+
+    #[test]
+    fn test_max_score() {
+        assert_eq!(self::create_board().max_score(), 24);
+
+        let content = "3 2\n3\n1\n1 1 1 1\n0 0 1 1\n0 2 3 1\n0 0 2 2\n0 0 1 1\n0 1 3 2\n0 0 2 2";
+        let board = BoardGame::new(content.lines().map(|line| line.to_string()).collect());
+        assert_eq!(board.max_score(), 7);
+    }
+
+    #[test]
+    fn test_score_empty_and_solved() {
+        let board = self::create_board();
+        assert_eq!(board.score(), 0);
+        assert!(board.mismatches().is_empty());
+
+        let solution = board.solve().unwrap();
+        assert_eq!(solution.score(), solution.max_score());
+        assert!(solution.mismatches().is_empty());
+    }
+
+    #[test]
+    fn test_score_partial() {
+        let mut board = self::create_board();
+        board.put_piece(0, (0, 0), None).unwrap();
+        board.put_piece(4, (1, 0), None).unwrap();
+        assert_eq!(board.score(), 1);
+
+        board.put_piece(8, (0, 1), None).unwrap();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        assert_eq!(board.score(), 2);
+        assert_eq!(
+            board.mismatches(),
+            vec![
+                ((0, 0), Border::South),
+                ((0, 1), Border::North),
+                ((0, 1), Border::East),
+                ((1, 1), Border::West),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_score_around() {
+        let solution = self::create_board().solve().unwrap();
+
+        assert_eq!(solution.score_around(&[(0, 0)]), 2);
+        assert_eq!(solution.score_around(&[(1, 1)]), 4);
+        assert_eq!(solution.score_around(&[(1, 1), (2, 1)]), 7);
+        assert_eq!(solution.score_around(&[(1, 1), (2, 2)]), 8);
+    }
+
+    #[test]
+    fn test_mismatches_rotation() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+        assert_eq!(board.score(), 0);
+        assert_eq!(
+            board.mismatches(),
+            vec![((1, 1), Border::East), ((2, 1), Border::West)]
+        );
+
+        board.rotate_piece((2, 1), Compass::West);
+        assert_eq!(board.cells[1][2].get_face(Border::West), Face::Color(3));
+        assert_eq!(board.score(), 1);
+        assert!(board.mismatches().is_empty());
+    }
+}
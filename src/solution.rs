@@ -0,0 +1,541 @@
+use std::fmt;
+
+use board::{BoardGame, Compass};
+use cell::{Border, Cell, CellKind, Face};
+use piece::PieceKind;
+use puzzle::{Line, ParseError, ParseErrorKind, Puzzle};
+
+/// Piece and orientation of each cell of a board, in row-major order.
+///
+/// The file starts with the width and height of the board, then gives one cell per line: the id
+/// of the piece and the direction its first side faces, one of `N`, `E`, `S` or `W`, or `-` for
+/// an empty cell. Blank lines are ignored.
+///
+/// ```text
+/// 2 2
+/// 0 E
+/// 1 S
+/// 3 N
+/// 2 W
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub width: u16,
+    pub height: u16,
+    pub cells: Vec<Option<(u16, Compass)>>,
+}
+
+/// Reason why a solution does not solve a puzzle
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The solution is for a board of another size, nothing else is checked
+    Dimensions {
+        expected: (u16, u16),
+        found: (u16, u16),
+    },
+    EmptyCell {
+        pos: (u16, u16),
+    },
+    UnknownPiece {
+        pos: (u16, u16),
+        id: u16,
+    },
+    /// The piece is already used at a previous cell
+    PieceReused {
+        pos: (u16, u16),
+        id: u16,
+    },
+    MissingPiece {
+        id: u16,
+    },
+    KindMismatch {
+        pos: (u16, u16),
+        piece: PieceKind,
+        cell: CellKind,
+    },
+    /// The side of the piece facing the frame is colored, or a side facing another piece is not
+    Frame {
+        pos: (u16, u16),
+        side: Border,
+    },
+    /// The side of the piece differs from the facing side of its east or south neighbor
+    EdgeMismatch {
+        pos: (u16, u16),
+        side: Border,
+        faces: (Face, Face),
+    },
+    /// A hint of the puzzle is not at its place
+    Hint {
+        pos: (u16, u16),
+        id: u16,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Dimensions { expected, found } => write!(
+                f,
+                "the board is {}x{}, the solution is {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            Problem::EmptyCell { pos } => write!(f, "no piece at {:?}", pos),
+            Problem::UnknownPiece { pos, id } => write!(f, "there is no piece {} at {:?}", id, pos),
+            Problem::PieceReused { pos, id } => {
+                write!(f, "piece {} at {:?} is already used", id, pos)
+            }
+            Problem::MissingPiece { id } => write!(f, "piece {} is not used", id),
+            Problem::KindMismatch { pos, piece, cell } => {
+                write!(f, "{} piece on {} cell {:?}", piece, cell, pos)
+            }
+            Problem::Frame { pos, side } => {
+                write!(f, "{:?} side of {:?} does not fit the frame", side, pos)
+            }
+            Problem::EdgeMismatch { pos, side, faces } => write!(
+                f,
+                "{:?} side of {:?} does not match: {:?} against {:?}",
+                side, pos, faces.0, faces.1
+            ),
+            Problem::Hint { pos, id } => write!(f, "hint {} is not placed at {:?}", id, pos),
+        }
+    }
+}
+
+impl Solution {
+    /// Reads a solution file
+    pub fn parse(content: &str) -> Result<Solution, ParseError> {
+        let lines = Line::split(content);
+        let end = content.lines().count() + 1;
+
+        let header = lines.first().ok_or(ParseError {
+            line: end,
+            column: 1,
+            kind: ParseErrorKind::MissingHeader("size"),
+        })?;
+        let dimensions = header.numbers(2)?;
+        for &(column, dimension) in &dimensions {
+            if dimension < 2 {
+                return Err(header.error(column, ParseErrorKind::InvalidSize(dimension)));
+            }
+        }
+        let (width, height) = (dimensions[0].1, dimensions[1].1);
+
+        let expected = (width as usize) * (height as usize);
+        let found = lines.len() - 1;
+        if found != expected {
+            let error = ParseErrorKind::PieceCount { expected, found };
+            return Err(match lines.get(1 + expected) {
+                Some(line) => line.error(line.start(), error),
+                None => ParseError {
+                    line: end,
+                    column: 1,
+                    kind: error,
+                },
+            });
+        }
+
+        let cells = lines[1..]
+            .iter()
+            .map(|line| {
+                if line.tokens.len() == 1 && line.tokens[0].1 == "-" {
+                    return Ok(None);
+                }
+                if line.tokens.len() != 2 {
+                    let column = line.tokens.get(2).unwrap_or(&line.tokens[0]).0;
+                    return Err(line.error(
+                        column,
+                        ParseErrorKind::ValueCount {
+                            expected: 2,
+                            found: line.tokens.len(),
+                        },
+                    ));
+                }
+
+                let id = line.number_at(0)?;
+                let (column, token) = line.tokens[1];
+                let compass = Compass::from_letter(token).ok_or_else(|| {
+                    line.error(column, ParseErrorKind::InvalidCompass(token.to_string()))
+                })?;
+                Ok(Some((id, compass)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Solution {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Returns the pieces placed on the board, with the orientation forced by the frame for
+    /// corner and border pieces
+    pub fn from_board(board: &BoardGame) -> Solution {
+        let cells = board
+            .cells
+            .iter()
+            .flat_map(|row| row.iter())
+            .map(|cell| {
+                let id = cell.get_props()?.id;
+                Some((id, cell.get_compass()?))
+            })
+            .collect();
+
+        Solution {
+            width: board.width,
+            height: board.height,
+            cells,
+        }
+    }
+
+    /// Returns the piece and orientation at the position
+    pub fn get(&self, pos: (u16, u16)) -> Option<(u16, Compass)> {
+        self.cells[(pos.1 as usize) * (self.width as usize) + (pos.0 as usize)]
+    }
+}
+
+impl fmt::Display for Solution {
+    /// Writes the solution in the format read by `Solution::parse`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {}", self.width, self.height)?;
+        for cell in &self.cells {
+            match cell {
+                Some((id, compass)) => writeln!(f, "{} {}", id, compass.letter())?,
+                None => writeln!(f, "-")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the solution solves the puzzle.
+///
+/// Every problem is reported, cell by cell in row-major order, then the unused pieces.
+pub fn verify(puzzle: &Puzzle, solution: &Solution) -> Result<(), Vec<Problem>> {
+    let (width, height) = (puzzle.width, puzzle.height);
+    if (solution.width, solution.height) != (width, height) {
+        return Err(vec![Problem::Dimensions {
+            expected: (width, height),
+            found: (solution.width, solution.height),
+        }]);
+    }
+
+    let mut problems = Vec::new();
+    let mut used = vec![false; puzzle.pieces.len()];
+    let faces = |pos: (u16, u16)| {
+        let (id, compass) = solution.get(pos)?;
+        let piece = puzzle.pieces.get(id as usize)?;
+        Some(piece.get_faces(&compass))
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let pos = (x, y);
+            let id = match solution.get(pos) {
+                Some((id, _)) => id,
+                None => {
+                    problems.push(Problem::EmptyCell { pos });
+                    continue;
+                }
+            };
+            let piece = match puzzle.pieces.get(id as usize) {
+                Some(piece) => piece,
+                None => {
+                    problems.push(Problem::UnknownPiece { pos, id });
+                    continue;
+                }
+            };
+
+            if used[id as usize] {
+                problems.push(Problem::PieceReused { pos, id });
+            }
+            used[id as usize] = true;
+
+            let cell = CellKind::at(pos, width, height);
+            if !Cell::empty(cell).accepts(piece) {
+                problems.push(Problem::KindMismatch {
+                    pos,
+                    piece: piece.get_kind(),
+                    cell,
+                });
+            }
+
+            let (north, east, south, west) = faces(pos).unwrap();
+            for &(side, face, on_frame) in &[
+                (Border::North, north, y == 0),
+                (Border::East, east, x == width - 1),
+                (Border::South, south, y == height - 1),
+                (Border::West, west, x == 0),
+            ] {
+                if on_frame != (face == Face::Border) {
+                    problems.push(Problem::Frame { pos, side });
+                }
+            }
+
+            for &(side, neighbor, face) in &[
+                (Border::East, (x + 1, y), east),
+                (Border::South, (x, y + 1), south),
+            ] {
+                if neighbor.0 >= width || neighbor.1 >= height {
+                    continue;
+                }
+                let other = match faces(neighbor) {
+                    Some((_, _, _, west)) if side == Border::East => west,
+                    Some((north, ..)) => north,
+                    None => continue,
+                };
+                if face != other {
+                    problems.push(Problem::EdgeMismatch {
+                        pos,
+                        side,
+                        faces: (face, other),
+                    });
+                }
+            }
+        }
+    }
+
+    for hint in &puzzle.hints {
+        let placed = solution.get(hint.pos);
+        let fits = match (placed, CellKind::at(hint.pos, width, height)) {
+            (Some((id, _)), CellKind::Corner(..)) | (Some((id, _)), CellKind::Border(_)) => {
+                id == hint.piece
+            }
+            (placed, CellKind::Full) => placed == Some((hint.piece, hint.compass)),
+            (None, _) => false,
+        };
+        if !fits {
+            problems.push(Problem::Hint {
+                pos: hint.pos,
+                id: hint.piece,
+            });
+        }
+    }
+
+    problems.extend(
+        used.iter()
+            .enumerate()
+            .filter(|(_, used)| !**used)
+            .map(|(id, _)| Problem::MissingPiece { id: id as u16 }),
+    );
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use puzzle::{Hint, PuzzleFile};
+
+    fn create_puzzle() -> Puzzle {
+        PuzzleFile::parse(include_str!("../pieces_4x4.txt")).unwrap()
+    }
+
+    fn create_solution() -> Solution {
+        let board = BoardGame::from_puzzle(self::create_puzzle());
+        Solution::from_board(&board.solve().unwrap())
+    }
+
+    #[test]
+    fn test_write_parse() {
+        let solution = self::create_solution();
+        let content = solution.to_string();
+
+        assert!(content.starts_with("4 4\n"));
+        assert_eq!(content.lines().count(), 17);
+        assert_eq!(Solution::parse(&content), Ok(solution));
+    }
+
+    #[test]
+    fn test_from_partial_board() {
+        let mut board = BoardGame::from_puzzle(self::create_puzzle());
+        board.put_piece(3, (0, 0), None).unwrap();
+        board.put_piece(12, (1, 1), Some(Compass::West)).unwrap();
+        let solution = Solution::from_board(&board);
+
+        assert_eq!(
+            solution.get((0, 0)),
+            board.cells[0][0].get_compass().map(|c| (3, c))
+        );
+        assert_eq!(solution.get((1, 1)), Some((12, Compass::West)));
+        assert_eq!(solution.get((2, 1)), None);
+        assert_eq!(Solution::parse(&solution.to_string()).unwrap(), solution);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |content: &str| {
+            let error = Solution::parse(content).unwrap_err();
+            (error.line, error.column, error.kind)
+        };
+
+        assert_eq!(error(""), (1, 1, ParseErrorKind::MissingHeader("size")));
+        assert_eq!(
+            error("2 2\n0 N\n1 E\n2 S\n"),
+            (
+                5,
+                1,
+                ParseErrorKind::PieceCount {
+                    expected: 4,
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(
+            error("2 2\n0 N\n1 E\n2 S 1\n3 W\n"),
+            (
+                4,
+                5,
+                ParseErrorKind::ValueCount {
+                    expected: 2,
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(
+            error("2 2\n0 N\n1 E\n2 S\n3 up\n"),
+            (5, 3, ParseErrorKind::InvalidCompass("up".to_string()))
+        );
+        assert_eq!(
+            error("2 2\n0 N\nx E\n2 S\n3 W\n"),
+            (3, 1, ParseErrorKind::InvalidNumber("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let puzzle = self::create_puzzle();
+        let solution = self::create_solution();
+
+        assert_eq!(verify(&puzzle, &solution), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_dimensions() {
+        let puzzle = self::create_puzzle();
+        let mut solution = self::create_solution();
+        solution.width = 8;
+        solution.height = 2;
+
+        assert_eq!(
+            verify(&puzzle, &solution),
+            Err(vec![Problem::Dimensions {
+                expected: (4, 4),
+                found: (8, 2)
+            }])
+        );
+    }
+
+    #[test]
+    fn test_verify_reports_every_problem() {
+        let puzzle = self::create_puzzle();
+        let solution = self::create_solution();
+
+        let mut broken = solution.clone();
+        let (first, _) = solution.get((0, 0)).unwrap();
+        broken.cells[1] = Some((first, Compass::North));
+        broken.cells[5] = None;
+        broken.cells[10] = Some((40, Compass::North));
+
+        let problems = verify(&puzzle, &broken).unwrap_err();
+        let (second, _) = solution.get((1, 0)).unwrap();
+        let (sixth, _) = solution.get((1, 1)).unwrap();
+        let (eleventh, _) = solution.get((2, 2)).unwrap();
+
+        assert!(problems.contains(&Problem::PieceReused {
+            pos: (1, 0),
+            id: first
+        }));
+        assert!(problems.contains(&Problem::KindMismatch {
+            pos: (1, 0),
+            piece: PieceKind::Corner,
+            cell: CellKind::Border(Border::North)
+        }));
+        assert!(problems.contains(&Problem::EmptyCell { pos: (1, 1) }));
+        assert!(problems.contains(&Problem::UnknownPiece {
+            pos: (2, 2),
+            id: 40
+        }));
+        assert!(problems
+            .iter()
+            .any(|problem| matches!(problem, Problem::Frame { pos: (1, 0), .. })));
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            Problem::EdgeMismatch {
+                pos: (0, 0),
+                side: Border::East,
+                ..
+            }
+        )));
+        let mut missing = vec![second, sixth, eleventh];
+        missing.sort();
+        assert_eq!(
+            problems[problems.len() - 3..],
+            missing
+                .into_iter()
+                .map(|id| Problem::MissingPiece { id })
+                .collect::<Vec<_>>()[..]
+        );
+    }
+
+    #[test]
+    fn test_verify_rotated_full_piece() {
+        let puzzle = self::create_puzzle();
+        let mut solution = self::create_solution();
+        let (id, compass) = solution.get((1, 1)).unwrap();
+        let turned =
+            Compass::all()[(Compass::all().iter().position(|c| *c == compass).unwrap() + 2) % 4];
+        solution.cells[5] = Some((id, turned));
+
+        let problems = verify(&puzzle, &solution).unwrap_err();
+        assert!(!problems.is_empty());
+        assert!(problems.iter().all(|problem| match problem {
+            Problem::EdgeMismatch { pos, .. } => [(1, 0), (0, 1), (1, 1)].contains(pos),
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn test_verify_hints() {
+        let mut puzzle = self::create_puzzle();
+        let solution = self::create_solution();
+        let (id, compass) = solution.get((1, 1)).unwrap();
+        puzzle.hints.push(Hint {
+            piece: id,
+            pos: (1, 1),
+            compass,
+        });
+        assert_eq!(verify(&puzzle, &solution), Ok(()));
+
+        puzzle.hints[0].pos = (2, 2);
+        assert_eq!(
+            verify(&puzzle, &solution),
+            Err(vec![Problem::Hint { pos: (2, 2), id }])
+        );
+    }
+
+    #[test]
+    fn test_problem_display() {
+        assert_eq!(
+            Problem::EdgeMismatch {
+                pos: (1, 2),
+                side: Border::East,
+                faces: (Face::Color(3), Face::Color(4))
+            }
+            .to_string(),
+            "East side of (1, 2) does not match: Color(3) against Color(4)"
+        );
+        assert_eq!(
+            Problem::KindMismatch {
+                pos: (0, 0),
+                piece: PieceKind::Full,
+                cell: CellKind::Corner(Border::North, Border::West)
+            }
+            .to_string(),
+            "full piece on corner cell (0, 0)"
+        );
+    }
+}
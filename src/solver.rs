@@ -0,0 +1,1005 @@
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use board::{BoardGame, Compass};
+use mrv::{CandidateCounts, Degrees};
+use piece::Piece;
+use propagation::Domains;
+use scan::ScanOrder;
+
+/// Search state of one cell of the scan order
+#[derive(Debug)]
+struct Frame {
+    pos: (u16, u16),
+    candidates: Vec<(u16, Compass)>,
+    next: usize,
+    placed: bool,
+}
+
+/// Outcome of `Solver::search`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchStatus {
+    /// The board is complete, the next search continues after this solution
+    Solution,
+    /// Every solution has been reported
+    Exhausted,
+    /// The node budget is spent, the next search continues from there
+    Paused,
+    /// The stop flag is set, see `Solver::stop_on`
+    Stopped,
+}
+
+/// Position of a search, enough to resume it on a copy of the board it started from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchState {
+    pub order: Vec<(u16, u16)>,
+    pub pinned: Option<(u16, Vec<(u16, u16)>)>,
+    pub started: bool,
+    /// For each filled cell of the order: the index of the next candidate to try and the piece
+    /// placed, if any
+    pub frames: Vec<(usize, Option<(u16, Compass)>)>,
+    pub nodes: u64,
+    /// The search keeps the domains of the cells, see `Solver::propagate`
+    pub propagate: bool,
+    /// The search fills the most constrained cell first, see `Solver::most_constrained`
+    pub mrv: bool,
+}
+
+/// Depth-first backtracking search over the empty cells of a board.
+///
+/// Cells are filled following a `ScanOrder`, row-major by default. At each cell the unplaced
+/// pieces of `BoardGame::candidates` are tried, using `BoardGame::place_piece` to check the whole
+/// neighborhood. With `Solver::propagate`, the candidates are also checked against the domains of
+/// the other cells. With `Solver::most_constrained`, the order only gives the cells to fill, the
+/// next cell is chosen as the search goes.
+///
+/// The solver is an iterator over every solution of the board.
+#[derive(Debug)]
+pub struct Solver {
+    board: BoardGame,
+    order: Vec<(u16, u16)>,
+    stack: Vec<Frame>,
+    started: bool,
+    /// Corner piece and the cells it is restricted to when rotations are grouped
+    pinned: Option<(u16, Vec<(u16, u16)>)>,
+    /// Flags ending the search once one of them is set
+    stop: Vec<Arc<AtomicBool>>,
+    /// Number of placements tried
+    nodes: u64,
+    /// Keeps the domains of the cells once the search starts
+    propagate: bool,
+    /// Feasible pieces of each cell, when propagating, computed once the search starts
+    domains: Option<Domains>,
+    /// Fills next the cell with the fewest candidates
+    mrv: bool,
+    /// Candidates of each cell without propagation, counted once the search starts
+    counts: Option<CandidateCounts>,
+    /// Empty neighbors of each cell, once the search starts
+    degrees: Option<Degrees>,
+    /// Number of cells of the order to fill to report a solution
+    target: usize,
+}
+
+impl Solver {
+    /// Creates a solver working on its own copy of the board.
+    ///
+    /// Cells which already contain a piece are kept as is. The moves of the board are forgotten
+    /// and the search does not record its own, the solutions record as the board did, see
+    /// `BoardGame::set_recording`.
+    pub fn new(board: BoardGame) -> Self {
+        Solver::with_order(board, &ScanOrder::RowMajor)
+    }
+
+    /// Creates a solver filling the cells in the given order
+    pub fn with_order(mut board: BoardGame, order: &ScanOrder) -> Self {
+        board.discard_history();
+        let order = board
+            .iter_cells(order)
+            .filter(|(_, cell)| cell.is_empty())
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+
+        Self {
+            board,
+            target: order.len(),
+            order,
+            stack: Vec::new(),
+            started: false,
+            pinned: None,
+            stop: Vec::new(),
+            nodes: 0,
+            propagate: false,
+            domains: None,
+            mrv: false,
+            counts: None,
+            degrees: None,
+        }
+    }
+
+    /// Reports only one solution out of the rotations of the whole board.
+    ///
+    /// A square board has four rotations, the representative holds the lowest corner piece in
+    /// the north-west corner. A rectangular board only has a half turn, the representative holds
+    /// this piece in a northern corner. The other corners are never tried with this piece.
+    /// Boards with pieces already placed are not symmetric, every solution is reported.
+    pub fn group_rotations(mut self) -> Self {
+        let (width, height) = (self.board.width, self.board.height);
+        if self.order.len() != (width as usize) * (height as usize) {
+            return self;
+        }
+
+        let cells = if width == height {
+            vec![(0, 0)]
+        } else {
+            vec![(0, 0), (width - 1, 0)]
+        };
+        self.pinned = self
+            .board
+            .pieces
+            .iter()
+            .find_map(|piece| match piece {
+                Piece::CornerPiece(props) => Some(props.id),
+                _ => None,
+            })
+            .map(|id| (id, cells));
+        self
+    }
+
+    /// Propagates the edge constraints through the domains of the empty cells, see `Domains`.
+    ///
+    /// Candidates are taken from the domain of each cell, and a placement leaving a domain empty
+    /// is undone at once, without going deeper. Propagation costs time at each placement but
+    /// prunes most dead ends of large boards. The domains are computed once the search starts.
+    pub fn propagate(mut self) -> Self {
+        self.propagate = true;
+        self
+    }
+
+    /// Fills next the empty cell with the fewest candidates, the most constrained one.
+    ///
+    /// The candidates of a cell are the unplaced oriented pieces matching its whole frontier, or
+    /// its domain with `Solver::propagate`. Ties go to the cell with the most empty neighbors, then
+    /// to the first cell in row-major order. The counts are updated at each placement, only around
+    /// the moved piece.
+    pub fn most_constrained(mut self) -> Self {
+        self.mrv = true;
+        self
+    }
+
+    /// Ends the search as if it was exhausted once the flag is set, from this thread or another.
+    ///
+    /// The flag is added to the ones already given, any of them stops the search.
+    pub fn stop_on(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop.push(stop);
+        self
+    }
+
+    /// Splits the search in the sub-searches left once the next `depth` cells are filled.
+    ///
+    /// Each sub-search starts from its own copy of the board and the solutions of all of them are
+    /// the solutions of this solver. The split has to be done before the search starts.
+    ///
+    /// # Panics
+    ///
+    /// if the search has started
+    pub fn split(mut self, depth: usize) -> Vec<Solver> {
+        assert!(!self.started, "cannot split a started search");
+
+        self.target = depth.min(self.order.len());
+        let mut parts = Vec::new();
+        while let Some(mut board) = self.next_solution() {
+            board.discard_history();
+            let order = self.order[self.target..].to_vec();
+            parts.push(Solver {
+                board,
+                target: order.len(),
+                order,
+                stack: Vec::new(),
+                started: false,
+                pinned: self.pinned.clone(),
+                stop: self.stop.clone(),
+                nodes: 0,
+                propagate: self.propagate,
+                domains: None,
+                mrv: self.mrv,
+                counts: None,
+                degrees: None,
+            });
+        }
+        parts
+    }
+
+    /// Returns the board in its current search state
+    pub fn board(&self) -> &BoardGame {
+        &self.board
+    }
+
+    /// Continues the search and returns the next solution, `None` once the search space is exhausted
+    pub fn next_solution(&mut self) -> Option<BoardGame> {
+        if self.advance() {
+            Some(self.board.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Counts the remaining solutions without copying the solved boards
+    pub fn count_solutions(&mut self) -> usize {
+        let mut count = 0;
+        while self.advance() {
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns the number of placements tried so far
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Continues the search for at most about `budget` placements.
+    ///
+    /// The search is resumed where the previous call stopped, whatever its status.
+    pub fn search(&mut self, budget: u64) -> SearchStatus {
+        let start = self.nodes;
+        if !self.started {
+            self.started = true;
+            // pieces put without checks are never moved, the search cannot fix their edges
+            if !self.board.mismatches().is_empty() {
+                return SearchStatus::Exhausted;
+            }
+            if self.propagate {
+                let mut domains = Domains::new(&self.board);
+                if !domains.propagate() {
+                    return SearchStatus::Exhausted;
+                }
+                self.domains = Some(domains);
+            }
+            self.count_candidates();
+            if self.target == 0 {
+                return SearchStatus::Solution;
+            }
+            self.push_frame();
+        }
+
+        loop {
+            if self.stop.iter().any(|stop| stop.load(Ordering::Relaxed)) {
+                return SearchStatus::Stopped;
+            }
+            if self.nodes - start >= budget {
+                return SearchStatus::Paused;
+            }
+
+            let top = match self.stack.last_mut() {
+                Some(top) => top,
+                None => return SearchStatus::Exhausted,
+            };
+
+            if top.placed {
+                self.board.remove_piece(top.pos);
+                top.placed = false;
+                if let Some(domains) = &mut self.domains {
+                    domains.undo();
+                }
+                if let Some(counts) = &mut self.counts {
+                    counts.remove(&self.board, top.pos, top.candidates[top.next - 1].0);
+                }
+                if let Some(degrees) = &mut self.degrees {
+                    degrees.remove(&self.board, top.pos);
+                }
+            }
+
+            while top.next < top.candidates.len() {
+                let (piece, compass) = top.candidates[top.next];
+                top.next += 1;
+                self.nodes += 1;
+
+                if self
+                    .board
+                    .place_piece(piece, top.pos, Some(compass))
+                    .is_err()
+                {
+                    continue;
+                }
+                if let Some(domains) = &mut self.domains {
+                    if !domains.assign(top.pos, piece, compass) {
+                        domains.undo();
+                        self.board.remove_piece(top.pos);
+                        continue;
+                    }
+                }
+                if let Some(counts) = &mut self.counts {
+                    counts.place(&self.board, top.pos, piece);
+                }
+                if let Some(degrees) = &mut self.degrees {
+                    degrees.place(&self.board, top.pos);
+                }
+                top.placed = true;
+                break;
+            }
+
+            if !top.placed {
+                self.stack.pop();
+            } else if self.stack.len() == self.target {
+                return SearchStatus::Solution;
+            } else {
+                self.push_frame();
+            }
+        }
+    }
+
+    /// Returns the position of the search, see `Solver::from_state`
+    pub fn state(&self) -> SearchState {
+        SearchState {
+            order: self.order.clone(),
+            pinned: self.pinned.clone(),
+            started: self.started,
+            frames: self
+                .stack
+                .iter()
+                .map(|frame| {
+                    let placed = if frame.placed {
+                        Some(frame.candidates[frame.next - 1])
+                    } else {
+                        None
+                    };
+                    (frame.next, placed)
+                })
+                .collect(),
+            nodes: self.nodes,
+            propagate: self.propagate,
+            mrv: self.mrv,
+        }
+    }
+
+    /// Resumes a search from its state, on a board in the state the search started from.
+    ///
+    /// The candidates of each cell are computed again and the placed pieces put back, no
+    /// placement is tried twice.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the state does not fit the board: an order which is not the set of empty cells of
+    /// the board, or pieces which are not the candidates the state points to.
+    pub fn from_state(mut board: BoardGame, state: SearchState) -> Option<Self> {
+        board.discard_history();
+        let SearchState {
+            order,
+            pinned,
+            started,
+            frames,
+            nodes,
+            propagate,
+            mrv,
+        } = state;
+
+        let size = (board.width, board.height);
+        let mut listed = vec![false; size.0 as usize * size.1 as usize];
+        let empty = board
+            .iter_cells(&ScanOrder::RowMajor)
+            .filter(|(_, cell)| cell.is_empty())
+            .count();
+        if frames.len() > order.len()
+            || (!started && !frames.is_empty())
+            || order.len() != empty
+            || order.iter().any(|&(x, y)| {
+                x >= size.0
+                    || y >= size.1
+                    || !board.cells[y as usize][x as usize].is_empty()
+                    || mem::replace(&mut listed[y as usize * size.0 as usize + x as usize], true)
+            })
+        {
+            return None;
+        }
+
+        let mut solver = Self {
+            board,
+            target: order.len(),
+            order,
+            stack: Vec::new(),
+            started,
+            pinned,
+            stop: Vec::new(),
+            nodes,
+            propagate,
+            domains: None,
+            mrv,
+            counts: None,
+            degrees: None,
+        };
+        if propagate && started {
+            let mut domains = Domains::new(&solver.board);
+            if !domains.propagate() && !frames.is_empty() {
+                return None;
+            }
+            solver.domains = Some(domains);
+        }
+        if started {
+            solver.count_candidates();
+        }
+        let depth = frames.len();
+        for (i, (next, placed)) in frames.into_iter().enumerate() {
+            solver.push_frame();
+            let top = solver.stack.last_mut().unwrap();
+            if next > top.candidates.len() {
+                return None;
+            }
+            top.next = next;
+
+            match placed {
+                Some(candidate) => {
+                    if next == 0 || top.candidates[next - 1] != candidate {
+                        return None;
+                    }
+                    let (piece, compass) = candidate;
+                    solver
+                        .board
+                        .place_piece(piece, top.pos, Some(compass))
+                        .ok()?;
+                    if let Some(domains) = &mut solver.domains {
+                        if !domains.assign(top.pos, piece, compass) {
+                            return None;
+                        }
+                    }
+                    if let Some(counts) = &mut solver.counts {
+                        counts.place(&solver.board, top.pos, piece);
+                    }
+                    if let Some(degrees) = &mut solver.degrees {
+                        degrees.place(&solver.board, top.pos);
+                    }
+                    top.placed = true;
+                }
+                // only the top frame can be left without a piece
+                None if i + 1 < depth => return None,
+                None => (),
+            }
+        }
+
+        Some(solver)
+    }
+
+    /// Moves the search to the next full board, returns `false` once the search space is exhausted
+    fn advance(&mut self) -> bool {
+        self.search(u64::MAX) == SearchStatus::Solution
+    }
+
+    /// Counts the candidates of the cells for `Solver::most_constrained`, when the domains do not,
+    /// and their empty neighbors
+    fn count_candidates(&mut self) {
+        if self.mrv {
+            if self.domains.is_none() {
+                self.counts = Some(CandidateCounts::new(&self.board));
+            }
+            self.degrees = Some(Degrees::new(&self.board));
+        }
+    }
+
+    /// Moves the most constrained cell left in the order to the next place to fill
+    fn choose_cell(&mut self) {
+        let depth = self.stack.len();
+        let degrees = match &self.degrees {
+            Some(degrees) => degrees,
+            None => return,
+        };
+        let count = |pos: (u16, u16)| match (&self.domains, &self.counts) {
+            (Some(domains), _) => domains.len(pos),
+            (None, Some(counts)) => counts.get(pos),
+            (None, None) => 0,
+        };
+
+        let best = (depth..self.order.len()).min_by_key(|&i| {
+            let (x, y) = self.order[i];
+            (count((x, y)), usize::MAX - degrees.get((x, y)), y, x)
+        });
+        if let Some(best) = best {
+            self.order.swap(depth, best);
+        }
+    }
+
+    /// Opens the next cell of the scan order with the unplaced candidates of the cell
+    fn push_frame(&mut self) {
+        if self.mrv {
+            self.choose_cell();
+        }
+        let pos = self.order[self.stack.len()];
+        let candidates = self
+            .board
+            .candidates(pos)
+            .iter()
+            .filter(|(id, _)| !self.board.placed[*id as usize])
+            .filter(|&&(id, compass)| match &self.domains {
+                Some(domains) => domains.contains(pos, id, compass),
+                None => true,
+            })
+            .filter(|(id, _)| match &self.pinned {
+                Some((pinned, cells)) => pinned != id || cells.contains(&pos),
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        self.stack.push(Frame {
+            pos,
+            candidates,
+            next: 0,
+            placed: false,
+        });
+    }
+}
+
+impl Iterator for Solver {
+    type Item = BoardGame;
+
+    fn next(&mut self) -> Option<BoardGame> {
+        self.next_solution()
+    }
+}
+
+impl BoardGame {
+    /// Searches the first complete solution of the board.
+    ///
+    /// Pieces already on the board are kept, the board itself is left untouched.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the board cannot be completed.
+    pub fn solve(&self) -> Option<BoardGame> {
+        Solver::new(self.clone()).next_solution()
+    }
+
+    /// Iterates over every solution of the board.
+    ///
+    /// With `group_rotations`, a single solution is reported for the four rotations of the board.
+    pub fn solutions(&self, group_rotations: bool) -> Solver {
+        let solver = Solver::new(self.clone());
+        if group_rotations {
+            solver.group_rotations()
+        } else {
+            solver
+        }
+    }
+
+    /// Counts the solutions of the board, see `BoardGame::solutions`
+    pub fn count_solutions(&self, group_rotations: bool) -> usize {
+        self.solutions(group_rotations).count_solutions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cell::{Border, Face};
+    use fixtures::create_board;
+    use generate::{generate, GeneratorConfig};
+    use puzzle::Hint;
+
+    fn assert_solved(board: &BoardGame) {
+        assert!(board.placed.iter().all(|placed| *placed));
+        for y in 0..board.height {
+            for x in 0..board.width {
+                let cell = &board.cells[y as usize][x as usize];
+                let (n, e, s, w) = board.get_frontier((x, y));
+                assert_eq!(cell.get_face(Border::North), n);
+                assert_eq!(cell.get_face(Border::East), e);
+                assert_eq!(cell.get_face(Border::South), s);
+                assert_eq!(cell.get_face(Border::West), w);
+                assert_ne!(n, Face::None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let board = self::create_board();
+        let solution = board.solve().expect("pieces_4x4 has a solution");
+
+        assert_solved(&solution);
+        assert!(board.placed.iter().all(|placed| !placed));
+    }
+
+    #[test]
+    fn test_solve_keeps_placed_pieces() {
+        let mut board = self::create_board();
+        board.put_piece(3, (0, 0), None).unwrap();
+
+        let solution = board.solve().unwrap();
+        assert_solved(&solution);
+        assert_eq!(solution.cells[0][0], board.cells[0][0]);
+    }
+
+    #[test]
+    fn test_solve_impossible() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+
+        assert_eq!(board.solve().map(|_| ()), None);
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let board = self::create_board();
+
+        let all = board.count_solutions(false);
+        let grouped = board.count_solutions(true);
+
+        assert!(grouped > 0);
+        assert_eq!(all, grouped * 4);
+        assert_eq!(board.solutions(false).count(), all);
+    }
+
+    #[test]
+    fn test_solutions_grouped() {
+        let board = self::create_board();
+
+        for solution in board.solutions(true) {
+            assert_solved(&solution);
+            assert_eq!(
+                solution.cells[0][0].get_props().map(|props| props.id),
+                Some(0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_solutions_rectangular() {
+        let content = "3 2\n3\n1\n1 1 1 1\n0 0 1 1\n0 2 3 1\n0 0 2 2\n0 0 1 1\n0 1 3 2\n0 0 2 2";
+        let board = BoardGame::new(content.lines().map(|line| line.to_string()).collect());
+
+        for solution in board.solutions(false) {
+            assert_solved(&solution);
+        }
+        let grouped = board.count_solutions(true);
+        assert!(grouped > 0);
+        assert_eq!(board.count_solutions(false), grouped * 2);
+    }
+
+    #[test]
+    fn test_count_solutions_placed() {
+        let mut board = self::create_board();
+        let all = board.count_solutions(false);
+        board.put_piece(0, (0, 0), None).unwrap();
+
+        assert_eq!(board.count_solutions(true), all / 4);
+        assert_eq!(board.count_solutions(false), all / 4);
+    }
+
+    #[test]
+    fn test_solve_hints() {
+        let mut board = self::create_board();
+        let all = board.count_solutions(false);
+        board
+            .add_hint(Hint {
+                piece: 0,
+                pos: (0, 0),
+                compass: Compass::North,
+            })
+            .unwrap();
+
+        let mut solver = Solver::new(board.clone()).group_rotations();
+        let solution = solver.next_solution().unwrap();
+        assert_solved(&solution);
+        assert_eq!(solution.hints, board.hints);
+        assert_eq!(solver.count_solutions() + 1, all / 4);
+        assert!(solver.board().is_locked((0, 0)));
+    }
+
+    #[test]
+    fn test_solve_scan_orders() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        let orders = vec![
+            ScanOrder::ColumnMajor,
+            ScanOrder::Spiral,
+            ScanOrder::BorderFirst,
+            ScanOrder::Diagonal,
+            ScanOrder::Custom(vec![(1, 1), (2, 2)]),
+        ];
+        for order in &orders {
+            let mut solver = Solver::with_order(board.clone(), order);
+            assert_solved(&solver.next_solution().unwrap());
+            assert_eq!(solver.count_solutions() + 1, all);
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        for depth in 0..4 {
+            let parts = Solver::new(board.clone()).split(depth);
+            assert!(parts
+                .iter()
+                .all(|part| part.board().placed.iter().filter(|p| **p).count() == depth));
+            let count: usize = parts
+                .into_iter()
+                .map(|mut part| part.count_solutions())
+                .sum();
+            assert_eq!(count, all);
+        }
+
+        let grouped: usize = board
+            .solutions(true)
+            .split(2)
+            .into_iter()
+            .map(|mut part| part.count_solutions())
+            .sum();
+        assert_eq!(grouped * 4, all);
+        assert_eq!(Solver::new(board.clone()).split(100).len(), all);
+    }
+
+    #[test]
+    fn test_solver_checks_filled_cells() {
+        let solution = self::create_board().solve().unwrap();
+        assert_eq!(Solver::new(solution.clone()).count_solutions(), 1);
+
+        let mut swapped = solution.clone();
+        let (a, b) = (
+            swapped.cells[1][1].get_props().unwrap().id,
+            swapped.cells[1][2].get_props().unwrap().id,
+        );
+        swapped.remove_piece((1, 1));
+        swapped.remove_piece((2, 1));
+        swapped.put_piece(b, (1, 1), Some(Compass::North)).unwrap();
+        swapped.put_piece(a, (2, 1), Some(Compass::North)).unwrap();
+        assert!(!swapped.mismatches().is_empty());
+        assert_eq!(Solver::new(swapped).count_solutions(), 0);
+
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+        assert!(board.solve().is_none());
+    }
+
+    #[test]
+    fn test_stop_on() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut solver = Solver::new(self::create_board()).stop_on(stop.clone());
+
+        assert!(solver.next_solution().is_some());
+        stop.store(true, Ordering::Relaxed);
+        assert!(solver.next_solution().is_none());
+
+        let other = Arc::new(AtomicBool::new(false));
+        let mut solver = Solver::new(self::create_board())
+            .stop_on(other.clone())
+            .stop_on(Arc::new(AtomicBool::new(false)));
+        assert!(solver.next_solution().is_some());
+        other.store(true, Ordering::Relaxed);
+        assert_eq!(solver.search(u64::MAX), SearchStatus::Stopped);
+    }
+
+    #[test]
+    fn test_search_budget() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+        let mut solver = Solver::new(board);
+
+        let (mut solutions, mut pauses) = (0, 0);
+        loop {
+            match solver.search(7) {
+                SearchStatus::Solution => solutions += 1,
+                SearchStatus::Paused => pauses += 1,
+                SearchStatus::Exhausted => break,
+                SearchStatus::Stopped => unreachable!(),
+            }
+        }
+        assert_eq!(solutions, all);
+        assert!(pauses > 0);
+        assert_eq!(solver.search(7), SearchStatus::Exhausted);
+    }
+
+    #[test]
+    fn test_state_resume() {
+        let board = self::create_board();
+        let all = board.count_solutions(true);
+
+        for budget in &[1, 50, 120, 1000] {
+            let mut solver = Solver::new(board.clone()).group_rotations();
+            let mut found = 0;
+            while solver.search(*budget) == SearchStatus::Solution {
+                found += 1;
+            }
+
+            let state = solver.state();
+            let mut resumed = Solver::from_state(board.clone(), state.clone()).unwrap();
+            assert_eq!(resumed.state(), state);
+            assert_eq!(resumed.board().cells, solver.board().cells);
+            assert_eq!(found + resumed.count_solutions(), all);
+            // the resumed search tries exactly the placements left
+            solver.count_solutions();
+            assert_eq!(resumed.nodes(), solver.nodes());
+        }
+    }
+
+    #[test]
+    fn test_state_mismatch() {
+        let board = self::create_board();
+        let mut solver = Solver::new(board.clone());
+        solver.next_solution().unwrap();
+        let mut state = solver.state();
+
+        let mut filled = board.clone();
+        filled.put_piece(0, (0, 0), None).unwrap();
+        assert!(Solver::from_state(filled, state.clone()).is_none());
+
+        let mut shortened = state.clone();
+        shortened.order.pop();
+        shortened.frames.truncate(shortened.order.len());
+        assert!(Solver::from_state(board.clone(), shortened).is_none());
+
+        let mut repeated = state.clone();
+        repeated.order[15] = repeated.order[0];
+        repeated.frames.truncate(1);
+        assert!(Solver::from_state(board.clone(), repeated).is_none());
+
+        state.frames[2].1 = Some((15, Compass::North));
+        assert!(Solver::from_state(board, state).is_none());
+    }
+
+    #[test]
+    fn test_propagate() {
+        let board = self::create_board();
+        let mut plain = Solver::new(board.clone());
+        let all = plain.count_solutions();
+
+        let mut solver = Solver::new(board.clone()).propagate();
+        assert_solved(&solver.next_solution().unwrap());
+        assert_eq!(solver.count_solutions() + 1, all);
+        assert!(solver.nodes() <= plain.nodes());
+        assert!(solver.board().placed.iter().all(|placed| !placed));
+
+        let grouped = Solver::with_order(board.clone(), &ScanOrder::Spiral)
+            .group_rotations()
+            .propagate()
+            .count_solutions();
+        assert_eq!(grouped * 4, all);
+
+        let parts = Solver::new(board).propagate().split(2);
+        // the domains of a part are only computed once its search starts
+        assert!(parts
+            .iter()
+            .all(|part| part.propagate && part.domains.is_none()));
+        let split: usize = parts
+            .into_iter()
+            .map(|mut part| part.count_solutions())
+            .sum();
+        assert_eq!(split, all);
+    }
+
+    #[test]
+    fn test_propagate_prunes() {
+        let (puzzle, _) = generate(&GeneratorConfig {
+            width: 5,
+            height: 5,
+            border_colors: 3,
+            inner_colors: 5,
+            seed: 2,
+        });
+        let board = BoardGame::from_puzzle(puzzle);
+
+        let mut plain = Solver::new(board.clone());
+        let mut solver = Solver::new(board).propagate();
+        assert_eq!(solver.count_solutions(), plain.count_solutions());
+        assert!(solver.nodes() < plain.nodes());
+    }
+
+    #[test]
+    fn test_propagate_impossible() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+
+        let mut solver = Solver::new(board).propagate();
+        assert_eq!(solver.search(u64::MAX), SearchStatus::Exhausted);
+        assert_eq!(solver.nodes(), 0);
+    }
+
+    #[test]
+    fn test_propagate_resume() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        for budget in &[1, 10, 40] {
+            let mut solver = Solver::new(board.clone()).propagate();
+            let mut found = 0;
+            while solver.search(*budget) == SearchStatus::Solution {
+                found += 1;
+            }
+
+            let state = solver.state();
+            assert!(state.propagate);
+            let mut resumed = Solver::from_state(board.clone(), state.clone()).unwrap();
+            assert_eq!(resumed.state(), state);
+            assert_eq!(found + resumed.count_solutions(), all);
+            solver.count_solutions();
+            assert_eq!(resumed.nodes(), solver.nodes());
+        }
+    }
+
+    #[test]
+    fn test_most_constrained() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        let mut solver = Solver::new(board.clone()).most_constrained();
+        assert_solved(&solver.next_solution().unwrap());
+        assert_eq!(solver.count_solutions() + 1, all);
+        assert!(solver.board().placed.iter().all(|placed| !placed));
+
+        let propagated = Solver::new(board.clone())
+            .most_constrained()
+            .propagate()
+            .count_solutions();
+        assert_eq!(propagated, all);
+
+        let split: usize = Solver::new(board)
+            .most_constrained()
+            .split(3)
+            .into_iter()
+            .map(|mut part| part.count_solutions())
+            .sum();
+        assert_eq!(split, all);
+    }
+
+    #[test]
+    fn test_most_constrained_prunes() {
+        let (puzzle, _) = generate(&GeneratorConfig {
+            width: 5,
+            height: 5,
+            border_colors: 3,
+            inner_colors: 5,
+            seed: 2,
+        });
+        let board = BoardGame::from_puzzle(puzzle);
+
+        let mut plain = Solver::new(board.clone());
+        let mut solver = Solver::new(board).most_constrained();
+        assert_eq!(solver.count_solutions(), plain.count_solutions());
+        assert!(solver.nodes() < plain.nodes());
+    }
+
+    #[test]
+    fn test_most_constrained_resume() {
+        let board = self::create_board();
+        let all = board.count_solutions(false);
+
+        for &propagate in &[false, true] {
+            for budget in &[1, 10, 40] {
+                let mut solver = Solver::new(board.clone()).most_constrained();
+                if propagate {
+                    solver = solver.propagate();
+                }
+                let mut found = 0;
+                while solver.search(*budget) == SearchStatus::Solution {
+                    found += 1;
+                }
+
+                let state = solver.state();
+                assert!(state.mrv);
+                let mut resumed = Solver::from_state(board.clone(), state.clone()).unwrap();
+                assert_eq!(resumed.state(), state);
+                assert_eq!(found + resumed.count_solutions(), all);
+                solver.count_solutions();
+                assert_eq!(resumed.nodes(), solver.nodes());
+            }
+        }
+    }
+
+    #[test]
+    fn test_solver_exhausts() {
+        let mut solver = Solver::new(self::create_board());
+
+        let mut count = 0;
+        while solver.next_solution().is_some() {
+            count += 1;
+        }
+
+        assert!(count > 0);
+        assert!(solver.next_solution().is_none());
+        assert!(solver.board().placed.iter().all(|placed| !placed));
+    }
+}
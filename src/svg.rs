@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use board::BoardGame;
+use cell::{Border, Face};
+
+/// Settings of `BoardGame::to_svg`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Side of a cell, in pixels
+    pub cell_size: u32,
+    /// Fill of each color, color `1` being the first, cycled through when there are more colors
+    pub palette: Vec<String>,
+    /// Writes the id of each piece in its center
+    pub show_ids: bool,
+    /// Writes the direction the first side of each piece faces
+    pub show_rotations: bool,
+    /// Outlines the mismatching sides in red
+    pub show_mismatches: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        let palette = [
+            "#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4", "#46f0f0", "#f032e6",
+            "#bcf60c", "#fabebe", "#008080", "#e6beff", "#9a6324", "#fffac8", "#800000", "#aaffc3",
+            "#808000", "#ffd8b1", "#000075", "#ffffff", "#000000", "#a9a9a9",
+        ];
+
+        Self {
+            cell_size: 40,
+            palette: palette.iter().map(|color| color.to_string()).collect(),
+            show_ids: false,
+            show_rotations: false,
+            show_mismatches: false,
+        }
+    }
+}
+
+/// Fill of the sides facing the frame
+const FRAME: &str = "#808080";
+
+impl BoardGame {
+    /// Draws the board as an SVG image.
+    ///
+    /// Each piece is cut in four triangles, one per side, like the physical pieces. Empty cells
+    /// are hatched.
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = options.cell_size;
+        let (width, height) = (self.width as u32 * size, self.height as u32 * size);
+        let mismatches: HashSet<_> = if options.show_mismatches {
+            self.mismatches().into_iter().collect()
+        } else {
+            HashSet::new()
+        };
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+            width, height
+        )
+        .unwrap();
+        svg +=
+            "<defs><pattern id=\"hatch\" width=\"6\" height=\"6\" patternUnits=\"userSpaceOnUse\" \
+                patternTransform=\"rotate(45)\"><rect width=\"6\" height=\"6\" fill=\"#ffffff\"/>\
+                <line x1=\"0\" y1=\"0\" x2=\"0\" y2=\"6\" stroke=\"#c0c0c0\" stroke-width=\"2\"/>\
+                </pattern></defs>\n";
+
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                let (x0, y0) = (column as u32 * size, row as u32 * size);
+                let (x1, y1) = (x0 + size, y0 + size);
+                let center = (x0 + size / 2, y0 + size / 2);
+
+                let props = match cell.get_props() {
+                    Some(props) => props,
+                    None => {
+                        writeln!(
+                            svg,
+                            "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"url(#hatch)\" stroke=\"#000000\"/>",
+                            x0, y0, size
+                        )
+                        .unwrap();
+                        continue;
+                    }
+                };
+
+                let sides = [
+                    (Border::North, (x0, y0), (x1, y0)),
+                    (Border::East, (x1, y0), (x1, y1)),
+                    (Border::South, (x1, y1), (x0, y1)),
+                    (Border::West, (x0, y1), (x0, y0)),
+                ];
+                for &(side, a, b) in &sides {
+                    let fill = match cell.get_face(side) {
+                        Face::Color(color) if !options.palette.is_empty() => {
+                            options.palette[(color as usize - 1) % options.palette.len()].as_str()
+                        }
+                        Face::Color(_) | Face::None => "#ffffff",
+                        Face::Border => FRAME,
+                    };
+                    writeln!(
+                        svg,
+                        "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\" stroke=\"#000000\"/>",
+                        a.0, a.1, b.0, b.1, center.0, center.1, fill
+                    )
+                    .unwrap();
+                }
+
+                for &(side, a, b) in &sides {
+                    if mismatches.contains(&((column as u16, row as u16), side)) {
+                        writeln!(
+                            svg,
+                            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#ff0000\" stroke-width=\"3\"/>",
+                            a.0, a.1, b.0, b.1
+                        )
+                        .unwrap();
+                    }
+                }
+
+                let mut labels = Vec::new();
+                if options.show_ids {
+                    labels.push(props.id.to_string());
+                }
+                if options.show_rotations {
+                    if let Some(compass) = cell.get_compass() {
+                        labels.push(compass.letter().to_string());
+                    }
+                }
+                if !labels.is_empty() {
+                    writeln!(
+                        svg,
+                        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>",
+                        center.0,
+                        center.1,
+                        (size / 4).max(1),
+                        labels.join(" ")
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Compass;
+    use fixtures::create_board;
+
+    #[test]
+    fn test_svg_empty() {
+        let svg = self::create_board().to_svg(&SvgOptions::default());
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"160\" height=\"160\" viewBox=\"0 0 160 160\">"
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("fill=\"url(#hatch)\"").count(), 16);
+        assert_eq!(svg.matches("<polygon").count(), 0);
+    }
+
+    #[test]
+    fn test_svg_pieces() {
+        let mut board = self::create_board();
+        board.put_piece(0, (0, 0), None).unwrap();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        let options = SvgOptions {
+            cell_size: 10,
+            palette: vec!["red".to_string(), "blue".to_string()],
+            ..SvgOptions::default()
+        };
+        let svg = board.to_svg(&options);
+
+        assert_eq!(svg.matches("<polygon").count(), 8);
+        assert_eq!(svg.matches("fill=\"url(#hatch)\"").count(), 14);
+        assert_eq!(svg.matches(&format!("fill=\"{}\"", FRAME)).count(), 2);
+        // piece 0 is `0 0 1 1`, piece 12 is `3 3 5 5`: colors 1, 3 and 5 are red
+        assert_eq!(svg.matches("fill=\"red\"").count(), 6);
+        assert!(
+            svg.contains("<polygon points=\"0,0 10,0 5,5\" fill=\"#808080\" stroke=\"#000000\"/>")
+        );
+        assert!(!svg.contains("<text"));
+        assert!(!svg.contains("stroke=\"#ff0000\""));
+    }
+
+    #[test]
+    fn test_svg_overlays() {
+        let mut board = self::create_board();
+        board.put_piece(12, (1, 1), Some(Compass::North)).unwrap();
+        board.put_piece(13, (2, 1), Some(Compass::North)).unwrap();
+        let options = SvgOptions {
+            show_ids: true,
+            show_rotations: true,
+            show_mismatches: true,
+            ..SvgOptions::default()
+        };
+        let svg = board.to_svg(&options);
+
+        assert!(svg.contains(">12 N</text>"));
+        assert!(svg.contains(">13 N</text>"));
+        assert_eq!(svg.matches("stroke=\"#ff0000\"").count(), 2);
+        assert!(svg.contains("<line x1=\"80\" y1=\"40\" x2=\"80\" y2=\"80\" stroke=\"#ff0000\""));
+    }
+}